@@ -102,9 +102,9 @@ impl AdriveOpenFileBatchGetRequest {
         let url = format!("{}/adrive/v1.0/openFile/batch/get", self.api_host.deref());
         let body = AdriveOpenFileBatchGetRequestPost {
             file_list: self.file_list.clone(),
-            video_thumbnail_time: self.video_thumbnail_time.deref().clone(),
-            video_thumbnail_width: self.video_thumbnail_width.deref().clone(),
-            image_thumbnail_width: self.image_thumbnail_width.deref().clone(),
+            video_thumbnail_time: *self.video_thumbnail_time.deref(),
+            video_thumbnail_width: *self.video_thumbnail_width.deref(),
+            image_thumbnail_width: *self.image_thumbnail_width.deref(),
         };
         let resp = self
             .agent
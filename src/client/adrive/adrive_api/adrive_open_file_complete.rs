@@ -79,17 +79,17 @@ impl AdriveOpenFileCompleteRequest {
             .await?
             .json(&AdriveOpenFileCompleteRequestPost {
                 drive_id: if let Some(drive_id) = self.drive_id.deref() {
-                    drive_id.clone().into()
+                    drive_id.clone()
                 } else {
                     return Err(crate::Error::require_param_missing("drive_id"));
                 },
                 file_id: if let Some(file_id) = self.file_id.deref() {
-                    file_id.clone().into()
+                    file_id.clone()
                 } else {
                     return Err(crate::Error::require_param_missing("file_id"));
                 },
                 upload_id: if let Some(upload_id) = self.upload_id.deref() {
-                    upload_id.clone().into()
+                    upload_id.clone()
                 } else {
                     return Err(crate::Error::require_param_missing("upload_id"));
                 },
@@ -1,4 +1,6 @@
-use crate::{response, AccessTokenLoader, AdriveClient, LoadAccessToken, OptionParam};
+use crate::{
+    response, AccessTokenLoader, AdriveAsyncTask, AdriveClient, LoadAccessToken, OptionParam,
+};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -108,7 +110,7 @@ impl AdriveOpenFileCopyRequest {
                 } else {
                     return Err(crate::Error::require_param_missing("to_parent_file_id"));
                 },
-                auto_rename: self.auto_rename.clone(),
+                auto_rename: *self.auto_rename,
             })
             .send()
             .await?;
@@ -122,3 +124,9 @@ pub struct AdriveOpenFileCopy {
     pub file_id: String,
     pub async_task_id: Option<String>,
 }
+
+impl AdriveAsyncTask for AdriveOpenFileCopy {
+    fn async_task_id(&self) -> Option<&str> {
+        self.async_task_id.as_deref()
+    }
+}
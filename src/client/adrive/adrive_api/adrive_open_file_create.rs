@@ -2,8 +2,8 @@ use crate::client::common::access_token_loader::AccessTokenLoader;
 use crate::response::null_to_default;
 use crate::{
     response, AdriveClient, AdriveOpenFileCreatePost, AdriveOpenFilePartInfoCreate,
-    AdriveOpenFilePartInfoUpload, AdriveOpenFileStreamInfo, AdriveOpenFileType, CheckNameMode,
-    LoadAccessToken, OptionParam,
+    AdriveOpenFilePartInfoUpload, AdriveOpenFileStreamInfo, AdriveOpenFileType, AdrivePathCache,
+    CheckNameMode, LoadAccessToken, OptionParam,
 };
 use chrono::Utc;
 use serde_derive::{Deserialize, Serialize};
@@ -16,6 +16,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             parent_file_id: None.into(),
             name: None.into(),
@@ -39,6 +40,7 @@ pub struct AdriveOpenFileCreateRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub parent_file_id: OptionParam<String>,
     pub name: OptionParam<String>,
@@ -75,25 +77,25 @@ impl AdriveOpenFileCreateRequest {
                 return Err(crate::Error::require_param_missing("name"));
             },
             r#type: if let Some(r#type) = self.r#type.deref() {
-                r#type.clone()
+                *r#type
             } else {
                 return Err(crate::Error::require_param_missing("r#type"));
             },
             check_name_mode: if let Some(check_name_mode) = self.check_name_mode.deref() {
-                check_name_mode.clone()
+                *check_name_mode
             } else {
                 return Err(crate::Error::require_param_missing("check_name_mode"));
             },
             part_info_list: self.part_info_list.clone(),
             streams_info: self.streams_info.clone(),
             pre_hash: self.pre_hash.clone(),
-            size: self.size.clone(),
+            size: *self.size,
             content_hash: self.content_hash.clone(),
             content_hash_name: self.content_hash_name.clone(),
             proof_code: self.proof_code.clone(),
             proof_version: self.proof_version.clone(),
-            local_created_at: self.local_created_at.clone(),
-            local_modified_at: self.local_modified_at.clone(),
+            local_created_at: *self.local_created_at,
+            local_modified_at: *self.local_modified_at,
         };
         let resp = self
             .agent
@@ -103,7 +105,16 @@ impl AdriveOpenFileCreateRequest {
             .json(&params)
             .send()
             .await?;
-        response(resp).await
+        let create: AdriveOpenFileCreate = response(resp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            path_cache.insert_child(
+                create.drive_id.as_str(),
+                create.parent_file_id.as_str(),
+                create.file_name.as_str(),
+                create.file_id.as_str(),
+            );
+        }
+        Ok(create)
     }
 }
 
@@ -1,4 +1,7 @@
-use crate::{response, AccessTokenLoader, AdriveClient, LoadAccessToken, OptionParam};
+use crate::{
+    response, AccessTokenLoader, AdriveAsyncTask, AdriveClient, AdrivePathCache, LoadAccessToken,
+    OptionParam,
+};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -9,6 +12,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_id: None.into(),
         }
@@ -20,6 +24,7 @@ pub struct AdriveOpenFileDeleteRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_id: OptionParam<String>,
 }
@@ -82,7 +87,11 @@ impl AdriveOpenFileDeleteRequest {
             })
             .send()
             .await?;
-        response(rsp).await
+        let delete: AdriveOpenFileDelete = response(rsp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            path_cache.invalidate(delete.drive_id.as_str(), delete.file_id.as_str());
+        }
+        Ok(delete)
     }
 }
 
@@ -92,3 +101,9 @@ pub struct AdriveOpenFileDelete {
     pub file_id: String,
     pub async_task_id: Option<String>,
 }
+
+impl AdriveAsyncTask for AdriveOpenFileDelete {
+    fn async_task_id(&self) -> Option<&str> {
+        self.async_task_id.as_deref()
+    }
+}
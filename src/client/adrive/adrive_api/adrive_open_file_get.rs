@@ -1,5 +1,6 @@
 use crate::{
-    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, Error, LoadAccessToken, OptionParam,
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, AdrivePathCache, Error,
+    LoadAccessToken, OptionParam,
 };
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -11,6 +12,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_id: None.into(),
             video_thumbnail_time: None.into(),
@@ -26,6 +28,7 @@ pub struct AdriveOpenFileGetRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_id: OptionParam<String>,
     pub video_thumbnail_time: OptionParam<i64>,
@@ -116,9 +119,9 @@ impl AdriveOpenFileGetRequest {
             } else {
                 return Err(Error::require_param_missing("file_id"));
             },
-            video_thumbnail_time: self.video_thumbnail_time.deref().clone(),
-            video_thumbnail_width: self.video_thumbnail_width.deref().clone(),
-            image_thumbnail_width: self.image_thumbnail_width.deref().clone(),
+            video_thumbnail_time: *self.video_thumbnail_time.deref(),
+            video_thumbnail_width: *self.video_thumbnail_width.deref(),
+            image_thumbnail_width: *self.image_thumbnail_width.deref(),
             fields: self.fields.deref().clone(),
         };
         let resp = self
@@ -129,6 +132,10 @@ impl AdriveOpenFileGetRequest {
             .json(&params)
             .send()
             .await?;
-        response(resp).await
+        let file: AdriveOpenFile = response(resp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            path_cache.insert_file(&file);
+        }
+        Ok(file)
     }
 }
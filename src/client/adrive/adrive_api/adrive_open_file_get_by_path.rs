@@ -1,5 +1,6 @@
 use crate::{
-    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, Error, LoadAccessToken, OptionParam,
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, AdrivePathCache, Error,
+    LoadAccessToken, OptionParam,
 };
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -11,6 +12,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_path: None.into(),
         }
@@ -22,6 +24,7 @@ pub struct AdriveOpenFileGetByPathRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_path: OptionParam<String>,
 }
@@ -89,6 +92,10 @@ impl AdriveOpenFileGetByPathRequest {
             })
             .send()
             .await?;
-        response(resp).await
+        let file: AdriveOpenFile = response(resp).await?;
+        if let (Some(path_cache), Some(file_path)) = (&self.path_cache, self.file_path.deref()) {
+            path_cache.insert(file.drive_id.as_str(), file_path, file.file_id.as_str());
+        }
+        Ok(file)
     }
 }
@@ -1,4 +1,4 @@
-use crate::{response, AccessTokenLoader, AdriveClient, LoadAccessToken, OptionParam};
+use crate::{response, AccessTokenLoader, AdriveClient, AlipanError, LoadAccessToken, OptionParam};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -72,7 +72,7 @@ impl AdriveOpenFileGetDownloadUrlRequest {
     pub async fn request(&self) -> crate::Result<AdriveOpenFileGetDownloadUrl> {
         let resp = self
             .agent
-            .post(&format!(
+            .post(format!(
                 "{}/adrive/v1.0/openFile/getDownloadUrl",
                 self.api_host
             ))
@@ -100,7 +100,22 @@ impl AdriveOpenFileGetDownloadUrlRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
 pub struct AdriveOpenFileGetDownloadUrl {
     pub url: String,
+    /// 过期时间，例如 2024-01-01T00:00:00.000Z，可以使用 expiration_at 解析
     pub expiration: String,
     pub method: String,
     pub description: Option<String>,
 }
+
+impl AdriveOpenFileGetDownloadUrl {
+    /// 解析后的过期时间
+    pub fn expiration_at(&self) -> crate::Result<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(self.expiration.as_str())
+            .map(|expiration| expiration.with_timezone(&chrono::Utc))
+            .map_err(|err| {
+                AlipanError::msg(format!(
+                    "invalid download url expiration {}: {}",
+                    self.expiration, err
+                ))
+            })
+    }
+}
@@ -83,10 +83,10 @@ impl AdriveOpenFileGetUploadUrlRequest {
     pub async fn request(&self) -> Result<AdriveOpenFileGetUploadUrl> {
         let url = format!("{}/adrive/v1.0/openFile/getUploadUrl", self.api_host);
         let post = AdriveOpenFileGetUploadUrlRequestPost {
-            drive_id: self.drive_id.clone().into(),
-            file_id: self.file_id.clone().into(),
-            upload_id: self.upload_id.clone().into(),
-            part_info_list: self.part_info_list.clone().into(),
+            drive_id: self.drive_id.clone(),
+            file_id: self.file_id.clone(),
+            upload_id: self.upload_id.clone(),
+            part_info_list: self.part_info_list.clone(),
         };
         let rsp = self
             .agent
@@ -0,0 +1,278 @@
+use crate::response::{lenient_datetime, lenient_i64, lenient_string, null_to_default};
+use crate::{response, AccessTokenLoader, AdriveClient, LoadAccessToken, OptionParam};
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// 未指定 url_expire_sec 时，服务端默认的播放地址有效期
+pub const ADRIVE_VIDEO_PREVIEW_DEFAULT_URL_EXPIRE_SEC: i64 = 900;
+
+impl AdriveClient {
+    /// 获取视频转码后的播放地址
+    pub async fn adrive_open_file_get_video_preview_play_info(
+        &self,
+    ) -> AdriveOpenFileGetVideoPreviewPlayInfoRequest {
+        AdriveOpenFileGetVideoPreviewPlayInfoRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: None.into(),
+            file_id: None.into(),
+            category: "live_transcoding".to_string(),
+            get_subtitle_info: None.into(),
+            template_id: None.into(),
+            url_expire_sec: None.into(),
+            only_vip: None.into(),
+            with_play_cursor: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdriveOpenFileGetVideoPreviewPlayInfoRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub drive_id: OptionParam<String>,
+    pub file_id: OptionParam<String>,
+    /// 默认为 live_transcoding
+    pub category: String,
+    pub get_subtitle_info: OptionParam<bool>,
+    /// 只获取一种清晰度，例如 LD、SD、HD、FHD、QHD，为空时获取全部
+    pub template_id: OptionParam<String>,
+    /// 播放地址的有效期，最长 4 小时
+    pub url_expire_sec: OptionParam<i64>,
+    pub only_vip: OptionParam<bool>,
+    pub with_play_cursor: OptionParam<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveOpenFileGetVideoPreviewPlayInfoRequestPost {
+    pub drive_id: String,
+    pub file_id: String,
+    pub category: String,
+    pub get_subtitle_info: Option<bool>,
+    pub template_id: Option<String>,
+    pub url_expire_sec: Option<i64>,
+    pub only_vip: Option<bool>,
+    pub with_play_cursor: Option<bool>,
+}
+
+impl AdriveOpenFileGetVideoPreviewPlayInfoRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn access_token(
+        mut self,
+        access_token: impl Into<Arc<Box<dyn AccessTokenLoader>>>,
+    ) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn file_id(mut self, file_id: impl Into<OptionParam<String>>) -> Self {
+        self.file_id = file_id.into();
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    pub fn get_subtitle_info(mut self, get_subtitle_info: impl Into<OptionParam<bool>>) -> Self {
+        self.get_subtitle_info = get_subtitle_info.into();
+        self
+    }
+
+    pub fn template_id(mut self, template_id: impl Into<OptionParam<String>>) -> Self {
+        self.template_id = template_id.into();
+        self
+    }
+
+    pub fn url_expire_sec(mut self, url_expire_sec: impl Into<OptionParam<i64>>) -> Self {
+        self.url_expire_sec = url_expire_sec.into();
+        self
+    }
+
+    pub fn only_vip(mut self, only_vip: impl Into<OptionParam<bool>>) -> Self {
+        self.only_vip = only_vip.into();
+        self
+    }
+
+    pub fn with_play_cursor(mut self, with_play_cursor: impl Into<OptionParam<bool>>) -> Self {
+        self.with_play_cursor = with_play_cursor.into();
+        self
+    }
+}
+
+impl AdriveOpenFileGetVideoPreviewPlayInfoRequest {
+    pub async fn request(&self) -> crate::Result<AdriveOpenFileGetVideoPreviewPlayInfo> {
+        let requested_at = Utc::now();
+        let resp = self
+            .agent
+            .post(
+                format!(
+                    "{}/adrive/v1.0/openFile/getVideoPreviewPlayInfo",
+                    self.api_host.as_str()
+                )
+                .as_str(),
+            )
+            .load_access_token(self.access_token.clone())
+            .await?
+            .json(&AdriveOpenFileGetVideoPreviewPlayInfoRequestPost {
+                drive_id: if let Some(drive_id) = self.drive_id.deref() {
+                    drive_id.clone()
+                } else {
+                    return Err(crate::Error::require_param_missing("drive_id"));
+                },
+                file_id: if let Some(file_id) = self.file_id.deref() {
+                    file_id.clone()
+                } else {
+                    return Err(crate::Error::require_param_missing("file_id"));
+                },
+                category: self.category.clone(),
+                get_subtitle_info: *self.get_subtitle_info.deref(),
+                template_id: self.template_id.deref().clone(),
+                url_expire_sec: *self.url_expire_sec.deref(),
+                only_vip: *self.only_vip.deref(),
+                with_play_cursor: *self.with_play_cursor.deref(),
+            })
+            .send()
+            .await?;
+        let mut play_info: AdriveOpenFileGetVideoPreviewPlayInfo = response(resp).await?;
+        // 服务端不返回过期时间，按请求的有效期计算
+        let expiration = requested_at
+            + chrono::Duration::seconds(
+                self.url_expire_sec
+                    .unwrap_or(ADRIVE_VIDEO_PREVIEW_DEFAULT_URL_EXPIRE_SEC),
+            );
+        let info = &mut play_info.video_preview_play_info;
+        for task in info.live_transcoding_task_list.iter_mut() {
+            task.expiration = task.expiration.or(task.url.as_ref().map(|_| expiration));
+        }
+        for task in info.live_transcoding_subtitle_task_list.iter_mut() {
+            task.expiration = task.expiration.or(task.url.as_ref().map(|_| expiration));
+        }
+        Ok(play_info)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AdriveOpenFileGetVideoPreviewPlayInfo {
+    pub drive_id: String,
+    pub file_id: String,
+    pub video_preview_play_info: AdriveVideoPreviewPlayInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoPreviewPlayInfo {
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub category: String,
+    #[serde(default)]
+    pub meta: Option<AdriveVideoPreviewPlayMetaInfo>,
+    /// 各个清晰度的转码任务
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub live_transcoding_task_list: Vec<AdriveVideoTranscodingTask>,
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub live_transcoding_subtitle_task_list: Vec<AdriveVideoSubtitleTask>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AdriveVideoPreviewPlayInfo {
+    /// 已经转码完成且有播放地址的任务，按分辨率从低到高排列
+    pub fn finished_tasks(&self) -> Vec<&AdriveVideoTranscodingTask> {
+        let mut tasks = self
+            .live_transcoding_task_list
+            .iter()
+            .filter(|task| task.is_finished() && task.url.is_some())
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|task| {
+            (
+                task.template_height.unwrap_or_default(),
+                task.template_width.unwrap_or_default(),
+            )
+        });
+        tasks
+    }
+
+    pub fn task(&self, template_id: &str) -> Option<&AdriveVideoTranscodingTask> {
+        self.live_transcoding_task_list
+            .iter()
+            .find(|task| task.template_id == template_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoPreviewPlayMetaInfo {
+    /// 秒，可能带小数
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub duration: Option<String>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub width: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub height: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoTranscodingTask {
+    /// 清晰度，例如 LD、SD、HD、FHD、QHD
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub template_id: String,
+    #[serde(default)]
+    pub template_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub template_width: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub template_height: Option<i64>,
+    /// finished、running、failed
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub status: String,
+    #[serde(default)]
+    pub stage: Option<String>,
+    /// m3u8 播放地址，play meta 接口不返回
+    #[serde(default)]
+    pub url: Option<String>,
+    /// 播放地址的过期时间
+    #[serde(default, deserialize_with = "lenient_datetime")]
+    pub expiration: Option<chrono::DateTime<Utc>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AdriveVideoTranscodingTask {
+    pub fn is_finished(&self) -> bool {
+        self.status == "finished"
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoSubtitleTask {
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub language: String,
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub status: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default, deserialize_with = "lenient_datetime")]
+    pub expiration: Option<chrono::DateTime<Utc>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,138 @@
+use crate::response::null_to_default;
+use crate::{
+    response, AccessTokenLoader, AdriveClient, AdriveVideoPreviewPlayMetaInfo,
+    AdriveVideoTranscodingTask, LoadAccessToken, OptionParam,
+};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl AdriveClient {
+    /// 获取视频的转码状态，不包含播放地址
+    pub async fn adrive_open_file_get_video_preview_play_meta(
+        &self,
+    ) -> AdriveOpenFileGetVideoPreviewPlayMetaRequest {
+        AdriveOpenFileGetVideoPreviewPlayMetaRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: None.into(),
+            file_id: None.into(),
+            category: "live_transcoding".to_string(),
+            template_id: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdriveOpenFileGetVideoPreviewPlayMetaRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub drive_id: OptionParam<String>,
+    pub file_id: OptionParam<String>,
+    /// 默认为 live_transcoding
+    pub category: String,
+    pub template_id: OptionParam<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveOpenFileGetVideoPreviewPlayMetaRequestPost {
+    pub drive_id: String,
+    pub file_id: String,
+    pub category: String,
+    pub template_id: Option<String>,
+}
+
+impl AdriveOpenFileGetVideoPreviewPlayMetaRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn access_token(
+        mut self,
+        access_token: impl Into<Arc<Box<dyn AccessTokenLoader>>>,
+    ) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn file_id(mut self, file_id: impl Into<OptionParam<String>>) -> Self {
+        self.file_id = file_id.into();
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    pub fn template_id(mut self, template_id: impl Into<OptionParam<String>>) -> Self {
+        self.template_id = template_id.into();
+        self
+    }
+}
+
+impl AdriveOpenFileGetVideoPreviewPlayMetaRequest {
+    pub async fn request(&self) -> crate::Result<AdriveOpenFileGetVideoPreviewPlayMeta> {
+        let resp = self
+            .agent
+            .post(
+                format!(
+                    "{}/adrive/v1.0/openFile/getVideoPreviewPlayMeta",
+                    self.api_host.as_str()
+                )
+                .as_str(),
+            )
+            .load_access_token(self.access_token.clone())
+            .await?
+            .json(&AdriveOpenFileGetVideoPreviewPlayMetaRequestPost {
+                drive_id: if let Some(drive_id) = self.drive_id.deref() {
+                    drive_id.clone()
+                } else {
+                    return Err(crate::Error::require_param_missing("drive_id"));
+                },
+                file_id: if let Some(file_id) = self.file_id.deref() {
+                    file_id.clone()
+                } else {
+                    return Err(crate::Error::require_param_missing("file_id"));
+                },
+                category: self.category.clone(),
+                template_id: self.template_id.deref().clone(),
+            })
+            .send()
+            .await?;
+        response(resp).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AdriveOpenFileGetVideoPreviewPlayMeta {
+    pub drive_id: String,
+    pub file_id: String,
+    pub video_preview_play_meta: AdriveVideoPreviewPlayMeta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoPreviewPlayMeta {
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub category: String,
+    #[serde(default)]
+    pub meta: Option<AdriveVideoPreviewPlayMetaInfo>,
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub live_transcoding_task_list: Vec<AdriveVideoTranscodingTask>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -1,8 +1,10 @@
+use crate::marker_stream::{marker_stream, MarkerRequest};
 use crate::response::AdriveOpenFileList;
 use crate::{
-    response, AccessTokenLoader, AdriveClient, AdriveOpenFileType, Error, LoadAccessToken,
-    OptionParam,
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, AdriveOpenFileType, AdrivePathCache,
+    Error, LoadAccessToken, OptionParam,
 };
+use futures::{Future, Stream};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -13,6 +15,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: "".to_string(),
             limit: None.into(),
             marker: None.into(),
@@ -33,6 +36,7 @@ pub struct AdriveOpenFileListRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: String,
     pub limit: OptionParam<i64>,
     pub marker: OptionParam<String>,
@@ -152,16 +156,16 @@ impl AdriveOpenFileListRequest {
         }
         let form = AdriveOpenFileListRequestPost {
             drive_id: self.drive_id.clone(),
-            limit: self.limit.deref().clone(),
+            limit: *self.limit.deref(),
             marker: self.marker.deref().clone(),
             order_by: self.order_by.deref().clone(),
             order_direction: self.order_direction.deref().clone(),
             parent_file_id: self.parent_file_id.clone(),
             category: self.category.deref().clone(),
-            r#type: self.r#type.deref().clone(),
-            video_thumbnail_time: self.video_thumbnail_time.deref().clone(),
-            video_thumbnail_width: self.video_thumbnail_width.deref().clone(),
-            image_thumbnail_width: self.image_thumbnail_width.deref().clone(),
+            r#type: *self.r#type.deref(),
+            video_thumbnail_time: *self.video_thumbnail_time.deref(),
+            video_thumbnail_width: *self.video_thumbnail_width.deref(),
+            image_thumbnail_width: *self.image_thumbnail_width.deref(),
             fields: self.fields.deref().clone(),
         };
         let url = url::Url::parse(
@@ -175,6 +179,29 @@ impl AdriveOpenFileListRequest {
             .json(&form)
             .send()
             .await?;
-        response(resp).await
+        let list: AdriveOpenFileList = response(resp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            list.items
+                .iter()
+                .for_each(|file| path_cache.insert_file(file));
+        }
+        Ok(list)
+    }
+
+    /// 从 marker 开始自动翻页，依次返回所有文件，limit 为每页的数量
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<AdriveOpenFile>> + Send {
+        marker_stream(self, |page| (page.items, page.next_marker))
+    }
+}
+
+impl MarkerRequest for AdriveOpenFileListRequest {
+    type Page = AdriveOpenFileList;
+
+    fn request_page(&self) -> impl Future<Output = crate::Result<Self::Page>> + Send {
+        self.request()
+    }
+
+    fn with_marker(self, marker: String) -> Self {
+        self.marker(marker)
     }
 }
@@ -1,4 +1,7 @@
+use crate::marker_stream::{marker_stream, MarkerRequest};
+use crate::response::{blank_to_null, null_to_default};
 use crate::{response, AdriveClient, BoxedAccessTokenLoader, LoadAccessToken, OptionParam};
+use futures::{Future, Stream};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use std::ops::Deref;
@@ -68,6 +71,14 @@ impl AdriveOpenFileListUploadedPartsRequest {
         self.upload_id = upload_id.into();
         self
     }
+
+    pub fn part_number_marker(
+        mut self,
+        part_number_marker: impl Into<OptionParam<String>>,
+    ) -> Self {
+        self.part_number_marker = part_number_marker.into();
+        self
+    }
 }
 
 impl AdriveOpenFileListUploadedPartsRequest {
@@ -94,13 +105,32 @@ impl AdriveOpenFileListUploadedPartsRequest {
                 } else {
                     return Err(crate::Error::require_param_missing("file_id"));
                 },
-                upload_id: self.upload_id.clone().into(),
+                upload_id: self.upload_id.clone(),
                 part_number_marker: self.part_number_marker.clone(),
             })
             .send()
             .await?;
         response(resp).await
     }
+
+    /// 从 part_number_marker 开始自动翻页，依次返回所有已上传的分片
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<UploadedParts>> + Send {
+        marker_stream(self, |page| {
+            (page.uploaded_parts, page.next_part_number_marker)
+        })
+    }
+}
+
+impl MarkerRequest for AdriveOpenFileListUploadedPartsRequest {
+    type Page = AdriveOpenFileListUploadedParts;
+
+    fn request_page(&self) -> impl Future<Output = crate::Result<Self::Page>> + Send {
+        self.request()
+    }
+
+    fn with_marker(self, marker: String) -> Self {
+        self.part_number_marker(marker)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
@@ -110,16 +140,21 @@ pub struct AdriveOpenFileListUploadedParts {
     pub upload_id: String,
     #[serde(rename = "parallelUpload")]
     pub parallel_upload: bool,
-    pub uploaded_parts: Vec<Value>,
+    #[serde(deserialize_with = "null_to_default")]
+    pub uploaded_parts: Vec<UploadedParts>,
+    #[serde(default, deserialize_with = "blank_to_null")]
     pub next_part_number_marker: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
 pub struct UploadedParts {
+    #[serde(default)]
     pub content_type: String,
     pub etag: Option<String>,
     pub part_number: i64,
+    #[serde(default)]
     pub part_size: i64,
     pub upload_form_info: Option<Value>,
+    #[serde(default)]
     pub upload_url: String,
 }
@@ -1,5 +1,6 @@
 use crate::{
-    response, AccessTokenLoader, AdriveClient, CheckNameMode, LoadAccessToken, OptionParam,
+    response, AccessTokenLoader, AdriveAsyncTask, AdriveClient, AdrivePathCache, CheckNameMode,
+    LoadAccessToken, OptionParam,
 };
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -11,6 +12,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_id: None.into(),
             to_parent_file_id: None.into(),
@@ -25,6 +27,7 @@ pub struct AdriveOpenFileMoveRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_id: OptionParam<String>,
     pub to_parent_file_id: OptionParam<String>,
@@ -112,12 +115,16 @@ impl AdriveOpenFileMoveRequest {
                 } else {
                     return Err(crate::Error::require_param_missing("to_parent_file_id"));
                 },
-                check_name_mode: self.check_name_mode.clone(),
+                check_name_mode: *self.check_name_mode,
                 new_name: self.new_name.clone(),
             })
             .send()
             .await?;
-        response(resp).await
+        let result: AdriveOpenFileMove = response(resp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            path_cache.invalidate(result.drive_id.as_str(), result.file_id.as_str());
+        }
+        Ok(result)
     }
 }
 
@@ -128,3 +135,9 @@ pub struct AdriveOpenFileMove {
     pub async_task_id: Option<String>,
     pub exist: bool,
 }
+
+impl AdriveAsyncTask for AdriveOpenFileMove {
+    fn async_task_id(&self) -> Option<&str> {
+        self.async_task_id.as_deref()
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    response, AccessTokenLoader, AdriveAsyncTask, AdriveClient, LoadAccessToken, OptionParam,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+impl AdriveClient {
+    /// 从回收站恢复
+    pub async fn adrive_open_file_recyclebin_restore(
+        &self,
+    ) -> AdriveOpenFileRecyclebinRestoreRequest {
+        AdriveOpenFileRecyclebinRestoreRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: None.into(),
+            file_id: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdriveOpenFileRecyclebinRestoreRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub drive_id: OptionParam<String>,
+    pub file_id: OptionParam<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveOpenFileRecyclebinRestorePost {
+    pub drive_id: String,
+    pub file_id: String,
+}
+
+impl AdriveOpenFileRecyclebinRestoreRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn access_token(
+        mut self,
+        access_token: impl Into<Arc<Box<dyn AccessTokenLoader>>>,
+    ) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn file_id(mut self, file_id: impl Into<OptionParam<String>>) -> Self {
+        self.file_id = file_id.into();
+        self
+    }
+}
+
+impl AdriveOpenFileRecyclebinRestoreRequest {
+    pub async fn request(&self) -> crate::Result<AdriveOpenFileRecyclebinRestore> {
+        let url = format!("{}/adrive/v1.0/openFile/recyclebin/restore", self.api_host);
+        let resp = self
+            .agent
+            .post(&url)
+            .load_access_token(self.access_token.clone())
+            .await?
+            .json(&AdriveOpenFileRecyclebinRestorePost {
+                drive_id: if let Some(drive_id) = self.drive_id.as_ref() {
+                    drive_id.to_string()
+                } else {
+                    return Err(crate::Error::require_param_missing("drive_id"));
+                },
+                file_id: if let Some(file_id) = self.file_id.as_ref() {
+                    file_id.to_string()
+                } else {
+                    return Err(crate::Error::require_param_missing("file_id"));
+                },
+            })
+            .send()
+            .await?;
+        response(resp).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveOpenFileRecyclebinRestore {
+    pub drive_id: String,
+    pub file_id: String,
+    pub async_task_id: Option<String>,
+}
+
+impl AdriveAsyncTask for AdriveOpenFileRecyclebinRestore {
+    fn async_task_id(&self) -> Option<&str> {
+        self.async_task_id.as_deref()
+    }
+}
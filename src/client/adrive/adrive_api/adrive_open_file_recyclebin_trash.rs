@@ -1,4 +1,7 @@
-use crate::{response, AccessTokenLoader, AdriveClient, LoadAccessToken, OptionParam};
+use crate::{
+    response, AccessTokenLoader, AdriveAsyncTask, AdriveClient, AdrivePathCache, LoadAccessToken,
+    OptionParam,
+};
 use serde_derive::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -8,6 +11,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_id: None.into(),
         }
@@ -19,6 +23,7 @@ pub struct AdriveOpenFileRecyclebinTrashRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_id: OptionParam<String>,
 }
@@ -81,7 +86,11 @@ impl AdriveOpenFileRecyclebinTrashRequest {
             })
             .send()
             .await?;
-        response(resp).await
+        let result: AdriveOpenFileRecyclebinTrash = response(resp).await?;
+        if let Some(path_cache) = &self.path_cache {
+            path_cache.invalidate(result.drive_id.as_str(), result.file_id.as_str());
+        }
+        Ok(result)
     }
 }
 
@@ -91,3 +100,9 @@ pub struct AdriveOpenFileRecyclebinTrash {
     pub file_id: String,
     pub async_task_id: Option<String>,
 }
+
+impl AdriveAsyncTask for AdriveOpenFileRecyclebinTrash {
+    fn async_task_id(&self) -> Option<&str> {
+        self.async_task_id.as_deref()
+    }
+}
@@ -0,0 +1,348 @@
+use crate::marker_stream::{marker_stream, MarkerRequest};
+use crate::response::blank_to_null;
+use crate::{
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, AdriveOpenFileType,
+    AdriveSearchField, AdriveSearchOperator, Error, LoadAccessToken, OptionParam,
+};
+use futures::{Future, Stream};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl AdriveClient {
+    pub async fn adrive_open_file_search(&self) -> AdriveOpenFileSearchRequest {
+        AdriveOpenFileSearchRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: None.into(),
+            limit: None.into(),
+            marker: None.into(),
+            order_by: None.into(),
+            query: None.into(),
+            video_thumbnail_time: None.into(),
+            video_thumbnail_width: None.into(),
+            image_thumbnail_width: None.into(),
+            return_total_count: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdriveOpenFileSearchRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub drive_id: OptionParam<String>,
+    pub limit: OptionParam<i64>,
+    pub marker: OptionParam<String>,
+    /// 例如 `updated_at DESC`
+    pub order_by: OptionParam<String>,
+    /// 查询语句，可以使用 AdriveSearchQuery 生成
+    pub query: OptionParam<String>,
+    pub video_thumbnail_time: OptionParam<i64>,
+    pub video_thumbnail_width: OptionParam<i64>,
+    pub image_thumbnail_width: OptionParam<i64>,
+    pub return_total_count: OptionParam<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveOpenFileSearchRequestPost {
+    pub drive_id: String,
+    pub limit: Option<i64>,
+    pub marker: Option<String>,
+    pub order_by: Option<String>,
+    pub query: Option<String>,
+    pub video_thumbnail_time: Option<i64>,
+    pub video_thumbnail_width: Option<i64>,
+    pub image_thumbnail_width: Option<i64>,
+    pub return_total_count: Option<bool>,
+}
+
+impl AdriveOpenFileSearchRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn access_token(
+        mut self,
+        access_token: impl Into<Arc<Box<dyn AccessTokenLoader>>>,
+    ) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn limit(mut self, limit: impl Into<OptionParam<i64>>) -> Self {
+        self.limit = limit.into();
+        self
+    }
+
+    pub fn marker(mut self, marker: impl Into<OptionParam<String>>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+
+    pub fn order_by(mut self, order_by: impl Into<OptionParam<String>>) -> Self {
+        self.order_by = order_by.into();
+        self
+    }
+
+    pub fn query(mut self, query: impl Into<OptionParam<String>>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    pub fn video_thumbnail_time(
+        mut self,
+        video_thumbnail_time: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.video_thumbnail_time = video_thumbnail_time.into();
+        self
+    }
+
+    pub fn video_thumbnail_width(
+        mut self,
+        video_thumbnail_width: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.video_thumbnail_width = video_thumbnail_width.into();
+        self
+    }
+
+    pub fn image_thumbnail_width(
+        mut self,
+        image_thumbnail_width: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.image_thumbnail_width = image_thumbnail_width.into();
+        self
+    }
+
+    pub fn return_total_count(mut self, return_total_count: impl Into<OptionParam<bool>>) -> Self {
+        self.return_total_count = return_total_count.into();
+        self
+    }
+}
+
+impl AdriveOpenFileSearchRequest {
+    pub async fn request(&self) -> crate::Result<AdriveOpenFileSearch> {
+        let form = AdriveOpenFileSearchRequestPost {
+            drive_id: if let Some(drive_id) = self.drive_id.deref() {
+                drive_id.clone()
+            } else {
+                return Err(Error::require_param_missing("drive_id"));
+            },
+            limit: *self.limit.deref(),
+            marker: self.marker.deref().clone(),
+            order_by: self.order_by.deref().clone(),
+            query: self.query.deref().clone(),
+            video_thumbnail_time: *self.video_thumbnail_time.deref(),
+            video_thumbnail_width: *self.video_thumbnail_width.deref(),
+            image_thumbnail_width: *self.image_thumbnail_width.deref(),
+            return_total_count: *self.return_total_count.deref(),
+        };
+        let resp = self
+            .agent
+            .post(format!("{}/adrive/v1.0/openFile/search", self.api_host.as_str()).as_str())
+            .load_access_token(self.access_token.clone())
+            .await?
+            .json(&form)
+            .send()
+            .await?;
+        response(resp).await
+    }
+
+    /// 从 marker 开始自动翻页，依次返回所有搜索结果，limit 为每页的数量
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<AdriveOpenFile>> + Send {
+        marker_stream(self, |page| (page.items, page.next_marker))
+    }
+}
+
+impl MarkerRequest for AdriveOpenFileSearchRequest {
+    type Page = AdriveOpenFileSearch;
+
+    fn request_page(&self) -> impl Future<Output = crate::Result<Self::Page>> + Send {
+        self.request()
+    }
+
+    fn with_marker(self, marker: String) -> Self {
+        self.marker(marker)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AdriveOpenFileSearch {
+    pub items: Vec<AdriveOpenFile>,
+    #[serde(deserialize_with = "blank_to_null")]
+    pub next_marker: Option<String>,
+    /// return_total_count 为 true 时返回
+    #[serde(default)]
+    pub total_count: Option<i64>,
+}
+
+/// 搜索的查询语句，值会被转义，例如
+///
+/// `AdriveSearchQuery::name_match("x").and(AdriveSearchQuery::r#type(AdriveOpenFileType::File))`
+/// 生成 `name match "x" and type = "file"`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdriveSearchQuery {
+    expression: String,
+    /// 是否由 and / or 组成，再次组合时需要加括号
+    compound: bool,
+}
+
+impl AdriveSearchQuery {
+    pub fn compare(
+        field: AdriveSearchField,
+        operator: AdriveSearchOperator,
+        value: impl AsRef<str>,
+    ) -> Self {
+        Self {
+            expression: format!(
+                "{} {} {}",
+                field.as_str(),
+                operator.as_str(),
+                quote(value.as_ref())
+            ),
+            compound: false,
+        }
+    }
+
+    /// 名称模糊匹配
+    pub fn name_match(name: impl AsRef<str>) -> Self {
+        Self::compare(AdriveSearchField::Name, AdriveSearchOperator::Match, name)
+    }
+
+    pub fn name(name: impl AsRef<str>) -> Self {
+        Self::compare(AdriveSearchField::Name, AdriveSearchOperator::Eq, name)
+    }
+
+    /// 后缀，不包含 `.`
+    pub fn file_extension(file_extension: impl AsRef<str>) -> Self {
+        Self::compare(
+            AdriveSearchField::FileExtension,
+            AdriveSearchOperator::Eq,
+            file_extension,
+        )
+    }
+
+    /// 例如 image、video、doc
+    pub fn category(category: impl AsRef<str>) -> Self {
+        Self::compare(
+            AdriveSearchField::Category,
+            AdriveSearchOperator::Eq,
+            category,
+        )
+    }
+
+    pub fn r#type(r#type: AdriveOpenFileType) -> Self {
+        Self::compare(
+            AdriveSearchField::Type,
+            AdriveSearchOperator::Eq,
+            r#type.as_str(),
+        )
+    }
+
+    /// 只搜索这个文件夹的直接子项
+    pub fn parent_file_id(parent_file_id: impl AsRef<str>) -> Self {
+        Self::compare(
+            AdriveSearchField::ParentFileId,
+            AdriveSearchOperator::Eq,
+            parent_file_id,
+        )
+    }
+
+    pub fn created_at(
+        operator: AdriveSearchOperator,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self::compare(
+            AdriveSearchField::CreatedAt,
+            operator,
+            format_time(created_at),
+        )
+    }
+
+    pub fn updated_at(
+        operator: AdriveSearchOperator,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self::compare(
+            AdriveSearchField::UpdatedAt,
+            operator,
+            format_time(updated_at),
+        )
+    }
+
+    pub fn and(self, other: AdriveSearchQuery) -> Self {
+        self.combine("and", other)
+    }
+
+    pub fn or(self, other: AdriveSearchQuery) -> Self {
+        self.combine("or", other)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.expression.as_str()
+    }
+
+    fn combine(self, logic: &str, other: AdriveSearchQuery) -> Self {
+        Self {
+            expression: format!("{} {} {}", self.group(), logic, other.group()),
+            compound: true,
+        }
+    }
+
+    fn group(self) -> String {
+        if self.compound {
+            format!("({})", self.expression)
+        } else {
+            self.expression
+        }
+    }
+}
+
+impl Display for AdriveSearchQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.expression.as_str())
+    }
+}
+
+impl From<AdriveSearchQuery> for String {
+    fn from(query: AdriveSearchQuery) -> Self {
+        query.expression
+    }
+}
+
+impl From<AdriveSearchQuery> for OptionParam<String> {
+    fn from(query: AdriveSearchQuery) -> Self {
+        OptionParam(Some(query.expression))
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn format_time(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
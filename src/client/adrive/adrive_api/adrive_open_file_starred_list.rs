@@ -0,0 +1,194 @@
+use crate::marker_stream::{marker_stream, MarkerRequest};
+use crate::response::AdriveOpenFileList;
+use crate::{
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFile, AdriveOpenFileType, Error,
+    LoadAccessToken, OptionParam,
+};
+use futures::{Future, Stream};
+use serde_derive::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl AdriveClient {
+    /// 收藏的文件列表
+    pub async fn adrive_open_file_starred_list(&self) -> AdriveOpenFileStarredListRequest {
+        AdriveOpenFileStarredListRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: None.into(),
+            limit: None.into(),
+            marker: None.into(),
+            order_by: None.into(),
+            order_direction: None.into(),
+            r#type: None.into(),
+            video_thumbnail_time: None.into(),
+            video_thumbnail_width: None.into(),
+            image_thumbnail_width: None.into(),
+            fields: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdriveOpenFileStarredListRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub drive_id: OptionParam<String>,
+    pub limit: OptionParam<i64>,
+    pub marker: OptionParam<String>,
+    pub order_by: OptionParam<String>,
+    pub order_direction: OptionParam<String>,
+    pub r#type: OptionParam<AdriveOpenFileType>,
+    pub video_thumbnail_time: OptionParam<i64>,
+    pub video_thumbnail_width: OptionParam<i64>,
+    pub image_thumbnail_width: OptionParam<i64>,
+    pub fields: OptionParam<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveOpenFileStarredListRequestPost {
+    pub drive_id: String,
+    pub limit: Option<i64>,
+    pub marker: Option<String>,
+    pub order_by: Option<String>,
+    pub order_direction: Option<String>,
+    pub r#type: Option<AdriveOpenFileType>,
+    pub video_thumbnail_time: Option<i64>,
+    pub video_thumbnail_width: Option<i64>,
+    pub image_thumbnail_width: Option<i64>,
+    pub fields: Option<String>,
+}
+
+impl AdriveOpenFileStarredListRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn access_token(
+        mut self,
+        access_token: impl Into<Arc<Box<dyn AccessTokenLoader>>>,
+    ) -> Self {
+        self.access_token = access_token.into();
+        self
+    }
+
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn limit(mut self, limit: impl Into<OptionParam<i64>>) -> Self {
+        self.limit = limit.into();
+        self
+    }
+
+    pub fn marker(mut self, marker: impl Into<OptionParam<String>>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+
+    pub fn order_by(mut self, order_by: impl Into<OptionParam<String>>) -> Self {
+        self.order_by = order_by.into();
+        self
+    }
+
+    pub fn order_direction(mut self, order_direction: impl Into<OptionParam<String>>) -> Self {
+        self.order_direction = order_direction.into();
+        self
+    }
+
+    pub fn r#type(mut self, r#type: impl Into<OptionParam<AdriveOpenFileType>>) -> Self {
+        self.r#type = r#type.into();
+        self
+    }
+
+    pub fn video_thumbnail_time(
+        mut self,
+        video_thumbnail_time: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.video_thumbnail_time = video_thumbnail_time.into();
+        self
+    }
+
+    pub fn video_thumbnail_width(
+        mut self,
+        video_thumbnail_width: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.video_thumbnail_width = video_thumbnail_width.into();
+        self
+    }
+
+    pub fn image_thumbnail_width(
+        mut self,
+        image_thumbnail_width: impl Into<OptionParam<i64>>,
+    ) -> Self {
+        self.image_thumbnail_width = image_thumbnail_width.into();
+        self
+    }
+
+    pub fn fields(mut self, fields: impl Into<OptionParam<String>>) -> Self {
+        self.fields = fields.into();
+        self
+    }
+}
+
+impl AdriveOpenFileStarredListRequest {
+    pub async fn request(&self) -> crate::Result<AdriveOpenFileList> {
+        let form = AdriveOpenFileStarredListRequestPost {
+            drive_id: if let Some(drive_id) = self.drive_id.deref() {
+                drive_id.clone()
+            } else {
+                return Err(Error::require_param_missing("drive_id"));
+            },
+            limit: *self.limit.deref(),
+            marker: self.marker.deref().clone(),
+            order_by: self.order_by.deref().clone(),
+            order_direction: self.order_direction.deref().clone(),
+            r#type: *self.r#type.deref(),
+            video_thumbnail_time: *self.video_thumbnail_time.deref(),
+            video_thumbnail_width: *self.video_thumbnail_width.deref(),
+            image_thumbnail_width: *self.image_thumbnail_width.deref(),
+            fields: self.fields.deref().clone(),
+        };
+        let resp = self
+            .agent
+            .post(
+                format!(
+                    "{}/adrive/v1.0/openFile/starredList",
+                    self.api_host.as_str()
+                )
+                .as_str(),
+            )
+            .load_access_token(self.access_token.clone())
+            .await?
+            .json(&form)
+            .send()
+            .await?;
+        response(resp).await
+    }
+
+    /// 从 marker 开始自动翻页，依次返回所有收藏的文件，limit 为每页的数量
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<AdriveOpenFile>> + Send {
+        marker_stream(self, |page| (page.items, page.next_marker))
+    }
+}
+
+impl MarkerRequest for AdriveOpenFileStarredListRequest {
+    type Page = AdriveOpenFileList;
+
+    fn request_page(&self) -> impl Future<Output = crate::Result<Self::Page>> + Send {
+        self.request()
+    }
+
+    fn with_marker(self, marker: String) -> Self {
+        self.marker(marker)
+    }
+}
@@ -1,7 +1,7 @@
 use crate::response::null_to_default;
 use crate::{
-    response, AccessTokenLoader, AdriveClient, AdriveOpenFileType, CheckNameMode, LoadAccessToken,
-    OptionParam,
+    response, AccessTokenLoader, AdriveClient, AdriveOpenFileType, AdrivePathCache, CheckNameMode,
+    LoadAccessToken, OptionParam,
 };
 use chrono::Utc;
 use serde_derive::{Deserialize, Serialize};
@@ -14,6 +14,7 @@ impl AdriveClient {
             agent: self.clone_agent().await,
             api_host: self.clone_api_host().await,
             access_token: self.clone_access_token_loader().await,
+            path_cache: self.clone_path_cache().await,
             drive_id: None.into(),
             file_id: None.into(),
             name: None.into(),
@@ -28,6 +29,7 @@ pub struct AdriveOpenFileUpdateRequest {
     pub agent: Arc<reqwest::Client>,
     pub api_host: Arc<String>,
     pub access_token: Arc<Box<dyn AccessTokenLoader>>,
+    pub(crate) path_cache: Option<AdrivePathCache>,
     pub drive_id: OptionParam<String>,
     pub file_id: OptionParam<String>,
     pub name: OptionParam<String>,
@@ -111,12 +113,17 @@ impl AdriveOpenFileUpdateRequest {
                     return Err(crate::Error::require_param_missing("file_id"));
                 },
                 name: self.name.clone(),
-                check_name_mode: self.check_name_mode.clone(),
-                starred: self.starred.clone(),
+                check_name_mode: *self.check_name_mode,
+                starred: *self.starred,
             })
             .send()
             .await?;
-        response(resp).await
+        let update: AdriveOpenFileUpdate = response(resp).await?;
+        // 只有改名会影响路径
+        if let (Some(path_cache), Some(_)) = (&self.path_cache, self.name.deref()) {
+            path_cache.invalidate(update.drive_id.as_str(), update.file_id.as_str());
+        }
+        Ok(update)
     }
 }
 
@@ -3,15 +3,20 @@ pub mod adrive_open_file_batch_get;
 pub mod adrive_open_file_complete;
 pub mod adrive_open_file_copy;
 pub mod adrive_open_file_create;
-mod adrive_open_file_delete;
+pub mod adrive_open_file_delete;
 pub mod adrive_open_file_get;
 pub mod adrive_open_file_get_by_path;
 pub mod adrive_open_file_get_download_url;
 pub mod adrive_open_file_get_upload_url;
+pub mod adrive_open_file_get_video_preview_play_info;
+pub mod adrive_open_file_get_video_preview_play_meta;
 pub mod adrive_open_file_list;
 pub mod adrive_open_file_list_uploaded_parts;
 pub mod adrive_open_file_move;
+pub mod adrive_open_file_recyclebin_restore;
 pub mod adrive_open_file_recyclebin_trash;
+pub mod adrive_open_file_search;
+pub mod adrive_open_file_starred_list;
 pub mod adrive_open_file_update;
 pub mod adrive_user_get_drive_info;
 pub mod adrive_user_get_space_info;
@@ -24,11 +29,18 @@ pub use adrive_open_file_batch_get::*;
 pub use adrive_open_file_complete::*;
 pub use adrive_open_file_copy::*;
 pub use adrive_open_file_create::*;
+pub use adrive_open_file_delete::*;
 pub use adrive_open_file_get::*;
 pub use adrive_open_file_get_upload_url::*;
+pub use adrive_open_file_get_video_preview_play_info::*;
+pub use adrive_open_file_get_video_preview_play_meta::*;
 pub use adrive_open_file_list::*;
 pub use adrive_open_file_list_uploaded_parts::*;
 pub use adrive_open_file_move::*;
+pub use adrive_open_file_recyclebin_restore::*;
+pub use adrive_open_file_recyclebin_trash::*;
+pub use adrive_open_file_search::*;
+pub use adrive_open_file_starred_list::*;
 pub use adrive_open_file_update::*;
 pub use adrive_user_get_drive_info::*;
 pub use adrive_user_get_space_info::*;
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::client::common::access_token_loader::BoxedAccessTokenLoader;
 use crate::define::DEFAULT_API_HOST;
+use crate::{AdrivePathCache, RateLimiter};
 use tokio::sync::Mutex;
 
 #[derive(Debug)]
@@ -10,6 +11,10 @@ pub struct AdriveClient {
     pub agent: Mutex<Arc<reqwest::Client>>,
     pub client_id: Mutex<Arc<String>>,
     pub access_token_loader: Mutex<Arc<BoxedAccessTokenLoader>>,
+    /// 这个客户端所有上传、下载共享的限速
+    pub rate_limiter: Mutex<Option<RateLimiter>>,
+    /// 路径与 file_id 的缓存，默认不启用
+    pub path_cache: Mutex<Option<AdrivePathCache>>,
 }
 
 impl Default for AdriveClient {
@@ -21,6 +26,8 @@ impl Default for AdriveClient {
             access_token_loader: Mutex::new(Arc::new(Box::new(
                 crate::access_token_loader::UninitializedAccessTokenLoader {},
             ))),
+            rate_limiter: Mutex::new(None),
+            path_cache: Mutex::new(None),
         }
     }
 }
@@ -49,6 +56,16 @@ impl AdriveClient {
         self
     }
 
+    pub async fn set_rate_limiter(self, rate_limiter: impl Into<Option<RateLimiter>>) -> Self {
+        *self.rate_limiter.lock().await = rate_limiter.into();
+        self
+    }
+
+    pub async fn set_path_cache(self, path_cache: impl Into<Option<AdrivePathCache>>) -> Self {
+        *self.path_cache.lock().await = path_cache.into();
+        self
+    }
+
     pub(crate) async fn clone_agent(&self) -> Arc<reqwest::Client> {
         self.agent.lock().await.clone()
     }
@@ -60,4 +77,12 @@ impl AdriveClient {
     pub(crate) async fn clone_access_token_loader(&self) -> Arc<BoxedAccessTokenLoader> {
         self.access_token_loader.lock().await.clone()
     }
+
+    pub(crate) async fn clone_rate_limiter(&self) -> Option<RateLimiter> {
+        self.rate_limiter.lock().await.clone()
+    }
+
+    pub(crate) async fn clone_path_cache(&self) -> Option<AdrivePathCache> {
+        self.path_cache.lock().await.clone()
+    }
 }
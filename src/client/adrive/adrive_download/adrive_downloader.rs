@@ -0,0 +1,434 @@
+use crate::adrive_download::download_sidecar::{load_sidecar, save_sidecar};
+use crate::adrive_open_file_get_download_url::AdriveOpenFileGetDownloadUrl;
+use crate::{
+    adrive_download_sidecar_path, adrive_source_content_hash, cancellable, progress_stream,
+    rate_limit_stream, AdriveClient, AdriveDownloadSidecar, AdriveOpenFile, AlipanError,
+    BoxedAdriveUploadSource, BoxedTransferProgress, CancellationToken, ErrorInfo, FileUploadSource,
+    OptionParam, RateLimiter,
+};
+use futures::{StreamExt, TryStreamExt};
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use std::io::SeekFrom;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// 默认每个分块 8MB
+pub const ADRIVE_DOWNLOAD_DEFAULT_CHUNK_SIZE: i64 = 8 << 20;
+/// 下载地址在过期前 1 分钟重新获取
+pub const ADRIVE_DOWNLOAD_URL_REFRESH_SECONDS: i64 = 60;
+
+impl AdriveClient {
+    pub async fn adrive_downloader(&self) -> AdriveDownloader<'_> {
+        AdriveDownloader {
+            client: self,
+            drive_id: None.into(),
+            file_id: None.into(),
+            file_path: None.into(),
+            chunk_size: ADRIVE_DOWNLOAD_DEFAULT_CHUNK_SIZE,
+            concurrency: 4,
+            retry_times: 3,
+            verify_content_hash: true,
+            resume: false,
+            progress: None.into(),
+            cancellation_token: None.into(),
+            rate_limiter: None.into(),
+        }
+    }
+}
+
+/// 下载文件：获取下载地址，按分块并发发送 Range 请求写入本地文件，最后校验 sha1
+///
+/// 取消或失败后，开启 resume 时可以再次下载继续未完成的部分
+#[derive(Debug)]
+pub struct AdriveDownloader<'a> {
+    pub client: &'a AdriveClient,
+    pub drive_id: OptionParam<String>,
+    pub file_id: OptionParam<String>,
+    /// 保存到的本地文件，已存在时会被覆盖
+    pub file_path: OptionParam<PathBuf>,
+    /// 每个 Range 请求的字节数
+    pub chunk_size: i64,
+    /// 同时下载的分块数
+    pub concurrency: usize,
+    /// 单个分块失败后的重试次数
+    pub retry_times: usize,
+    /// 下载完成后校验 content_hash
+    pub verify_content_hash: bool,
+    /// 断点续传：在 `{file_path}.adrive-download` 中记录已完成的区间，再次下载时只下载缺少的部分
+    pub resume: bool,
+    /// 下载进度回调，分块序号从 1 开始
+    pub progress: OptionParam<Arc<BoxedTransferProgress>>,
+    /// 取消后会中断正在下载的分块并返回 ErrorInfo::Cancelled
+    pub cancellation_token: OptionParam<CancellationToken>,
+    /// 只限制这次下载的速度，不设置时使用 AdriveClient 的限速
+    pub rate_limiter: OptionParam<RateLimiter>,
+}
+
+impl<'a> AdriveDownloader<'a> {
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn file_id(mut self, file_id: impl Into<OptionParam<String>>) -> Self {
+        self.file_id = file_id.into();
+        self
+    }
+
+    pub fn file_path(mut self, file_path: impl Into<PathBuf>) -> Self {
+        self.file_path = file_path.into().into();
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: i64) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn retry_times(mut self, retry_times: usize) -> Self {
+        self.retry_times = retry_times;
+        self
+    }
+
+    pub fn verify_content_hash(mut self, verify_content_hash: bool) -> Self {
+        self.verify_content_hash = verify_content_hash;
+        self
+    }
+
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn progress(
+        mut self,
+        progress: impl Into<OptionParam<Arc<BoxedTransferProgress>>>,
+    ) -> Self {
+        self.progress = progress.into();
+        self
+    }
+
+    pub fn cancellation_token(
+        mut self,
+        cancellation_token: impl Into<OptionParam<CancellationToken>>,
+    ) -> Self {
+        self.cancellation_token = cancellation_token.into();
+        self
+    }
+
+    pub fn rate_limiter(mut self, rate_limiter: impl Into<OptionParam<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter.into();
+        self
+    }
+}
+
+impl<'a> AdriveDownloader<'a> {
+    pub async fn download(&self) -> crate::Result<AdriveOpenFile> {
+        cancellable(self.cancellation_token.as_ref(), self.download_file()).await
+    }
+
+    async fn download_file(&self) -> crate::Result<AdriveOpenFile> {
+        let drive_id = if let Some(drive_id) = self.drive_id.deref() {
+            drive_id.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("drive_id"));
+        };
+        let file_id = if let Some(file_id) = self.file_id.deref() {
+            file_id.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("file_id"));
+        };
+        let file_path = if let Some(file_path) = self.file_path.deref() {
+            file_path.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("file_path"));
+        };
+        let file = self
+            .client
+            .adrive_open_file_get()
+            .await
+            .drive_id(drive_id.as_str())
+            .file_id(file_id.as_str())
+            .request()
+            .await?;
+        let sidecar_path = adrive_download_sidecar_path(&file_path);
+        let mut sidecar = AdriveDownloadSidecar {
+            drive_id: file.drive_id.clone(),
+            file_id: file.file_id.clone(),
+            size: file.size,
+            content_hash: file.content_hash.clone(),
+            completed: vec![],
+        };
+        if self.resume {
+            if let Some(saved) = load_sidecar(&sidecar_path).await? {
+                let local_size = tokio::fs::metadata(&file_path)
+                    .await
+                    .map(|metadata| metadata.len() as i64)
+                    .ok();
+                if saved.same_file(&sidecar) && local_size == Some(file.size) {
+                    sidecar = saved;
+                }
+            }
+        }
+        if sidecar.completed.is_empty() {
+            let local = tokio::fs::File::create(&file_path).await?;
+            local.set_len(file.size as u64).await?;
+        }
+        if self.resume {
+            save_sidecar(&sidecar_path, &sidecar).await?;
+        }
+        let missing = sidecar.missing();
+        if let Some(progress) = self.progress.deref() {
+            progress.on_start(Some(missing.iter().map(|(start, end)| end - start).sum()));
+        }
+        let download_url = tokio::sync::Mutex::new(
+            self.request_download_url(drive_id.as_str(), file_id.as_str())
+                .await?,
+        );
+        let agent = self.client.clone_agent().await;
+        let rate_limiter = match self.rate_limiter.deref() {
+            Some(rate_limiter) => Some(rate_limiter.clone()),
+            None => self.client.clone_rate_limiter().await,
+        };
+        let sidecar = tokio::sync::Mutex::new(sidecar);
+        let target = DownloadTarget {
+            agent: agent.as_ref(),
+            drive_id: drive_id.as_str(),
+            file_id: file_id.as_str(),
+            download_url: &download_url,
+            file_path: &file_path,
+            rate_limiter: &rate_limiter,
+            sidecar: if self.resume {
+                Some((&sidecar_path, &sidecar))
+            } else {
+                None
+            },
+        };
+        let chunk_size = self.chunk_size.max(1);
+        let chunks = missing
+            .into_iter()
+            .flat_map(|(start, end)| {
+                (start..end)
+                    .step_by(chunk_size as usize)
+                    .map(move |offset| (offset, chunk_size.min(end - offset)))
+            })
+            .enumerate()
+            .map(|(index, (offset, len))| DownloadChunk {
+                chunk_number: index as i64 + 1,
+                offset,
+                len,
+            })
+            .collect::<Vec<_>>();
+        futures::stream::iter(chunks)
+            .map(|chunk| self.download_chunk(target, chunk))
+            .buffer_unordered(self.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+        // 所有区间都已完成，校验失败时也不再续传
+        if self.resume {
+            tokio::fs::remove_file(&sidecar_path).await?;
+        }
+        if self.verify_content_hash && !file.content_hash.is_empty() {
+            let source: BoxedAdriveUploadSource = Box::new(FileUploadSource::new(&file_path));
+            let content_hash = adrive_source_content_hash(&source).await?;
+            if !content_hash.eq_ignore_ascii_case(&file.content_hash) {
+                return Err(AlipanError::new(ErrorInfo::ContentHashMismatch(
+                    file.content_hash.clone(),
+                    content_hash,
+                )));
+            }
+        }
+        Ok(file)
+    }
+
+    async fn request_download_url(
+        &self,
+        drive_id: &str,
+        file_id: &str,
+    ) -> crate::Result<AdriveOpenFileGetDownloadUrl> {
+        self.client
+            .adrive_open_file_get_download_url()
+            .await
+            .drive_id(drive_id)
+            .file_id(file_id)
+            .request()
+            .await
+    }
+
+    /// 当前的下载地址，即将过期或者 stale 被服务器拒绝时重新获取
+    async fn download_url(
+        &self,
+        target: DownloadTarget<'_>,
+        stale: Option<&str>,
+    ) -> crate::Result<String> {
+        let mut download_url = target.download_url.lock().await;
+        // 过期时间无法解析时只在服务器拒绝后重新获取
+        let expiring = download_url.expiration_at().is_ok_and(|expiration| {
+            expiration - chrono::Duration::seconds(ADRIVE_DOWNLOAD_URL_REFRESH_SECONDS)
+                <= chrono::Utc::now()
+        });
+        if stale == Some(download_url.url.as_str()) || expiring {
+            tracing::debug!("download url expired, reissue");
+            *download_url = self
+                .request_download_url(target.drive_id, target.file_id)
+                .await?;
+        }
+        Ok(download_url.url.clone())
+    }
+
+    /// 下载单个分块，失败时只重试这个分块，下载地址过期时重新获取
+    async fn download_chunk(
+        &self,
+        target: DownloadTarget<'_>,
+        chunk: DownloadChunk,
+    ) -> crate::Result<()> {
+        let mut retry = 0;
+        let mut reissue = 0;
+        let mut stale = None;
+        loop {
+            let url = self.download_url(target, stale.take().as_deref()).await?;
+            let received = Arc::new(AtomicU64::new(0));
+            let err = match self
+                .fetch_chunk(target, url.as_str(), &chunk, received.clone())
+                .await
+            {
+                Ok(()) => {
+                    if let Some((sidecar_path, sidecar)) = target.sidecar {
+                        let mut sidecar = sidecar.lock().await;
+                        sidecar.complete(chunk.offset, chunk.offset + chunk.len);
+                        save_sidecar(sidecar_path, &sidecar).await?;
+                    }
+                    if let Some(progress) = self.progress.deref() {
+                        progress.on_part_completed(chunk.chunk_number, chunk.len);
+                    }
+                    return Ok(());
+                }
+                Err(err) => err,
+            };
+            match err {
+                AlipanError {
+                    inner: ErrorInfo::DownloadUrlExpired(_),
+                    ..
+                } if reissue < self.retry_times => {
+                    reissue += 1;
+                    self.on_retry(&chunk, reissue, received.load(Ordering::Relaxed), &err);
+                    stale = Some(url);
+                }
+                err if retry < self.retry_times => {
+                    retry += 1;
+                    tracing::warn!(
+                        "download chunk {} failed, retry {}/{}: {}",
+                        chunk.chunk_number,
+                        retry,
+                        self.retry_times,
+                        err
+                    );
+                    self.on_retry(&chunk, retry, received.load(Ordering::Relaxed), &err);
+                    tokio::time::sleep(Duration::from_secs(retry as u64)).await;
+                }
+                err => return Err(err),
+            }
+        }
+    }
+
+    async fn fetch_chunk(
+        &self,
+        target: DownloadTarget<'_>,
+        url: &str,
+        chunk: &DownloadChunk,
+        received: Arc<AtomicU64>,
+    ) -> crate::Result<()> {
+        let resp = target
+            .agent
+            .get(url)
+            .header(
+                RANGE,
+                format!("bytes={}-{}", chunk.offset, chunk.offset + chunk.len - 1),
+            )
+            .send()
+            .await?;
+        let code = resp.status();
+        if code == StatusCode::FORBIDDEN {
+            return Err(AlipanError::new(ErrorInfo::DownloadUrlExpired(
+                chunk.offset,
+            )));
+        }
+        if code != StatusCode::PARTIAL_CONTENT {
+            let text = resp.text().await?;
+            return Err(AlipanError::server(code, text.as_str()));
+        }
+        let body = rate_limit_stream(
+            resp.bytes_stream().map_err(std::io::Error::other),
+            target.rate_limiter.clone(),
+        );
+        let mut body = std::pin::pin!(progress_stream(
+            body,
+            self.progress.deref().clone(),
+            received
+        ));
+        let mut local = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(target.file_path)
+            .await?;
+        local.seek(SeekFrom::Start(chunk.offset as u64)).await?;
+        let mut written = 0;
+        while let Some(bytes) = body.next().await {
+            let bytes = bytes?;
+            written += bytes.len() as i64;
+            if written > chunk.len {
+                return Err(AlipanError::msg(format!(
+                    "chunk {} too long: {} > {}",
+                    chunk.chunk_number, written, chunk.len
+                )));
+            }
+            local.write_all(&bytes).await?;
+        }
+        local.flush().await?;
+        if written != chunk.len {
+            return Err(AlipanError::msg(format!(
+                "chunk {} incomplete: {} < {}",
+                chunk.chunk_number, written, chunk.len
+            )));
+        }
+        // 记录为已完成之前确保数据已经写入磁盘
+        if target.sidecar.is_some() {
+            local.sync_data().await?;
+        }
+        Ok(())
+    }
+
+    fn on_retry(&self, chunk: &DownloadChunk, retry: usize, discarded: u64, err: &AlipanError) {
+        if let Some(progress) = self.progress.deref() {
+            progress.on_retry(chunk.chunk_number, retry, discarded, err);
+        }
+    }
+}
+
+/// 同一次下载中所有分块共用的参数
+#[derive(Clone, Copy)]
+struct DownloadTarget<'a> {
+    agent: &'a reqwest::Client,
+    drive_id: &'a str,
+    file_id: &'a str,
+    download_url: &'a tokio::sync::Mutex<AdriveOpenFileGetDownloadUrl>,
+    file_path: &'a PathBuf,
+    rate_limiter: &'a Option<RateLimiter>,
+    /// 续传时记录完成的区间
+    sidecar: Option<(&'a PathBuf, &'a tokio::sync::Mutex<AdriveDownloadSidecar>)>,
+}
+
+struct DownloadChunk {
+    chunk_number: i64,
+    offset: i64,
+    len: i64,
+}
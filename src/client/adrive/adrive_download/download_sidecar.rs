@@ -0,0 +1,86 @@
+use serde_derive::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 断点续传的记录文件后缀，保存在下载文件旁边
+pub const ADRIVE_DOWNLOAD_SIDECAR_EXTENSION: &str = "adrive-download";
+
+/// 下载中的文件已经完成的区间，下载完成后删除
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveDownloadSidecar {
+    pub drive_id: String,
+    pub file_id: String,
+    pub size: i64,
+    /// 云端文件变化后不会续传
+    pub content_hash: String,
+    /// 已完成的区间 [start, end)，按 start 排序且互不相邻
+    pub completed: Vec<(i64, i64)>,
+}
+
+impl AdriveDownloadSidecar {
+    /// 记录的是否是同一个文件的同一个版本
+    pub fn same_file(&self, other: &AdriveDownloadSidecar) -> bool {
+        self.drive_id == other.drive_id
+            && self.file_id == other.file_id
+            && self.size == other.size
+            && self.content_hash == other.content_hash
+            && self.completed.iter().all(|(_, end)| *end <= self.size)
+    }
+
+    /// 记录一个完成的区间，并与相邻的区间合并
+    pub fn complete(&mut self, start: i64, end: i64) {
+        self.completed.push((start, end));
+        self.completed.sort();
+        let mut merged: Vec<(i64, i64)> = Vec::with_capacity(self.completed.len());
+        for (start, end) in self.completed.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.completed = merged;
+    }
+
+    /// 还没有完成的区间
+    pub fn missing(&self) -> Vec<(i64, i64)> {
+        let mut missing = vec![];
+        let mut offset = 0;
+        for (start, end) in &self.completed {
+            if *start > offset {
+                missing.push((offset, *start));
+            }
+            offset = offset.max(*end);
+        }
+        if offset < self.size {
+            missing.push((offset, self.size));
+        }
+        missing
+    }
+}
+
+/// 下载文件对应的记录文件：`{file_path}.adrive-download`
+pub fn adrive_download_sidecar_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(ADRIVE_DOWNLOAD_SIDECAR_EXTENSION);
+    PathBuf::from(path)
+}
+
+pub(crate) async fn load_sidecar(path: &Path) -> crate::Result<Option<AdriveDownloadSidecar>> {
+    match tokio::fs::read(path).await {
+        Ok(data) => Ok(serde_json::from_slice(&data).ok()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// 先写临时文件再重命名，中断时不会留下写了一半的记录
+pub(crate) async fn save_sidecar(
+    path: &Path,
+    sidecar: &AdriveDownloadSidecar,
+) -> crate::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(sidecar)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
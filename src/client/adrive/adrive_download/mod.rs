@@ -0,0 +1,7 @@
+pub mod adrive_downloader;
+pub mod download_sidecar;
+pub mod remote_file_reader;
+
+pub use adrive_downloader::*;
+pub use download_sidecar::*;
+pub use remote_file_reader::*;
@@ -0,0 +1,247 @@
+use crate::adrive_open_file_get_download_url::{
+    AdriveOpenFileGetDownloadUrl, AdriveOpenFileGetDownloadUrlRequest,
+};
+use crate::{
+    AccessTokenLoader, AdriveClient, AdriveOpenFile, AlipanError,
+    ADRIVE_DOWNLOAD_URL_REFRESH_SECONDS,
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use std::fmt::{Debug, Formatter};
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// 默认每次请求至少读取 1MB
+pub const ADRIVE_REMOTE_FILE_READ_AHEAD_SIZE: usize = 1 << 20;
+
+impl AdriveClient {
+    /// 打开云端文件用于随机读取，不会下载整个文件
+    pub async fn adrive_remote_file_reader(
+        &self,
+        drive_id: impl Into<String>,
+        file_id: impl Into<String>,
+    ) -> crate::Result<RemoteFileReader> {
+        let source = Arc::new(RemoteFileSource {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            access_token: self.clone_access_token_loader().await,
+            drive_id: drive_id.into(),
+            file_id: file_id.into(),
+        });
+        let file = self
+            .adrive_open_file_get()
+            .await
+            .drive_id(source.drive_id.as_str())
+            .file_id(source.file_id.as_str())
+            .request()
+            .await?;
+        let download_url = source.request_download_url().await?;
+        Ok(RemoteFileReader {
+            source,
+            file,
+            read_ahead: ADRIVE_REMOTE_FILE_READ_AHEAD_SIZE,
+            position: 0,
+            buffer: Bytes::new(),
+            buffer_offset: 0,
+            download_url,
+            pending: None,
+        })
+    }
+}
+
+/// 云端文件的 AsyncRead + AsyncSeek，读取时按需发送 Range 请求
+///
+/// 每次请求至少读取 read_ahead 个字节并缓存，下载地址即将过期或者被拒绝时自动重新获取
+pub struct RemoteFileReader {
+    source: Arc<RemoteFileSource>,
+    file: AdriveOpenFile,
+    read_ahead: usize,
+    position: u64,
+    buffer: Bytes,
+    buffer_offset: u64,
+    download_url: AdriveOpenFileGetDownloadUrl,
+    pending: Option<BoxFuture<'static, crate::Result<FetchedRange>>>,
+}
+
+impl Debug for RemoteFileReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteFileReader")
+            .field("drive_id", &self.source.drive_id)
+            .field("file_id", &self.source.file_id)
+            .field("size", &self.file.size)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl RemoteFileReader {
+    pub fn read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead.max(1);
+        self
+    }
+
+    /// 打开时获取的文件信息
+    pub fn file(&self) -> &AdriveOpenFile {
+        &self.file
+    }
+
+    pub fn size(&self) -> u64 {
+        self.file.size as u64
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl AsyncRead for RemoteFileReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.position >= this.size() || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            let buffer_end = this.buffer_offset + this.buffer.len() as u64;
+            if this.position >= this.buffer_offset && this.position < buffer_end {
+                let start = (this.position - this.buffer_offset) as usize;
+                let len = buf.remaining().min(this.buffer.len() - start);
+                buf.put_slice(&this.buffer[start..start + len]);
+                this.position += len as u64;
+                return Poll::Ready(Ok(()));
+            }
+            let pending = this.pending.get_or_insert_with(|| {
+                let len = (buf.remaining().max(this.read_ahead) as u64)
+                    .min(this.file.size as u64 - this.position);
+                fetch_range(
+                    this.source.clone(),
+                    this.download_url.clone(),
+                    this.position,
+                    len,
+                )
+                .boxed()
+            });
+            let fetched = match pending.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(fetched) => fetched,
+            };
+            this.pending = None;
+            let fetched = fetched.map_err(std::io::Error::other)?;
+            if fetched.bytes.is_empty() {
+                return Poll::Ready(Err(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            this.download_url = fetched.download_url;
+            this.buffer_offset = fetched.offset;
+            this.buffer = fetched.bytes;
+        }
+    }
+}
+
+impl AsyncSeek for RemoteFileReader {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let this = self.get_mut();
+        let position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => this.size().checked_add_signed(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                // 正在进行的请求不再需要
+                this.pending = None;
+                this.position = position;
+                Ok(())
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+/// 读取需要的参数，不借用 AdriveClient，reader 可以移动到其他任务中
+struct RemoteFileSource {
+    agent: Arc<reqwest::Client>,
+    api_host: Arc<String>,
+    access_token: Arc<Box<dyn AccessTokenLoader>>,
+    drive_id: String,
+    file_id: String,
+}
+
+impl RemoteFileSource {
+    async fn request_download_url(&self) -> crate::Result<AdriveOpenFileGetDownloadUrl> {
+        AdriveOpenFileGetDownloadUrlRequest {
+            agent: self.agent.clone(),
+            api_host: self.api_host.clone(),
+            access_token: self.access_token.clone(),
+            drive_id: self.drive_id.as_str().into(),
+            file_id: self.file_id.as_str().into(),
+            expire_sec: None.into(),
+        }
+        .request()
+        .await
+    }
+}
+
+struct FetchedRange {
+    download_url: AdriveOpenFileGetDownloadUrl,
+    offset: u64,
+    bytes: Bytes,
+}
+
+async fn fetch_range(
+    source: Arc<RemoteFileSource>,
+    mut download_url: AdriveOpenFileGetDownloadUrl,
+    offset: u64,
+    len: u64,
+) -> crate::Result<FetchedRange> {
+    let mut refreshed = false;
+    loop {
+        // 过期时间无法解析时不主动刷新，等服务器返回 403 再重新获取
+        let expiring = download_url.expiration_at().is_ok_and(|expiration| {
+            expiration - chrono::Duration::seconds(ADRIVE_DOWNLOAD_URL_REFRESH_SECONDS)
+                <= chrono::Utc::now()
+        });
+        if !refreshed && expiring {
+            download_url = source.request_download_url().await?;
+            refreshed = true;
+        }
+        let resp = source
+            .agent
+            .get(download_url.url.as_str())
+            .header(RANGE, format!("bytes={}-{}", offset, offset + len - 1))
+            .send()
+            .await?;
+        let code = resp.status();
+        if code == StatusCode::FORBIDDEN && !refreshed {
+            tracing::debug!("download url expired, reissue");
+            download_url = source.request_download_url().await?;
+            refreshed = true;
+            continue;
+        }
+        if code != StatusCode::PARTIAL_CONTENT {
+            let text = resp.text().await?;
+            return Err(AlipanError::server(code, text.as_str()));
+        }
+        let bytes = resp.bytes().await?;
+        return Ok(FetchedRange {
+            download_url,
+            offset,
+            bytes,
+        });
+    }
+}
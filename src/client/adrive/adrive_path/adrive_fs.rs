@@ -0,0 +1,170 @@
+use crate::{
+    AdriveClient, AdriveOpenFile, AdriveOpenFileCopy, AdriveOpenFileMove,
+    AdriveOpenFileRecyclebinTrash, AdriveOpenFileType, AlipanError, CheckNameMode, ErrorInfo,
+};
+use futures::TryStreamExt;
+
+impl AdriveClient {
+    /// 以路径操作一个云盘中的文件，路径用 `/` 分隔，例如 `/backups/2026/db.tar`
+    pub async fn adrive_fs(&self, drive_id: impl Into<String>) -> AdriveFs<'_> {
+        AdriveFs {
+            client: self,
+            drive_id: drive_id.into(),
+        }
+    }
+}
+
+/// 基于路径的文件操作，内部通过 get_by_path 把路径转换为 file_id
+#[derive(Debug)]
+pub struct AdriveFs<'a> {
+    pub client: &'a AdriveClient,
+    pub drive_id: String,
+}
+
+impl<'a> AdriveFs<'a> {
+    /// 获取路径对应的文件信息，不存在时返回 PathNotFound
+    pub async fn stat(&self, path: &str) -> crate::Result<AdriveOpenFile> {
+        let path = normalize_path(path);
+        self.client
+            .adrive_open_file_get_by_path()
+            .await
+            .drive_id(self.drive_id.as_str())
+            .file_path(path.as_str())
+            .request()
+            .await
+            .map_err(|err| not_found(err, path.as_str()))
+    }
+
+    /// 逐级创建文件夹，已经存在的文件夹直接使用，返回最后一级的 file_id
+    pub async fn mkdir_p(&self, path: &str) -> crate::Result<String> {
+        let path_cache = self.client.clone_path_cache().await;
+        let mut parent_file_id = "root".to_string();
+        let mut current = String::new();
+        for name in path_components(path) {
+            current = format!("{}/{}", current, name);
+            if let Some(file_id) = path_cache
+                .as_ref()
+                .and_then(|path_cache| path_cache.file_id(self.drive_id.as_str(), &current))
+            {
+                parent_file_id = file_id;
+                continue;
+            }
+            let create = self
+                .client
+                .adrive_open_file_create()
+                .await
+                .drive_id(self.drive_id.as_str())
+                .parent_file_id(parent_file_id)
+                .name(name)
+                .r#type(AdriveOpenFileType::Folder)
+                .check_name_mode(CheckNameMode::Refuse)
+                .request()
+                .await?;
+            parent_file_id = create.file_id;
+        }
+        Ok(parent_file_id)
+    }
+
+    /// 列出文件夹下的全部文件
+    pub async fn read_dir(&self, path: &str) -> crate::Result<Vec<AdriveOpenFile>> {
+        let parent_file_id = self.resolve(path).await?;
+        self.client
+            .adrive_open_file_list()
+            .await
+            .drive_id(self.drive_id.as_str())
+            .parent_file_id(parent_file_id)
+            .into_stream()
+            .try_collect()
+            .await
+    }
+
+    /// 移动到回收站
+    pub async fn remove(&self, path: &str) -> crate::Result<AdriveOpenFileRecyclebinTrash> {
+        let file_id = self.resolve(path).await?;
+        self.client
+            .adrive_open_file_recyclebin_trash()
+            .await
+            .drive_id(self.drive_id.as_str())
+            .file_id(file_id)
+            .request()
+            .await
+    }
+
+    /// 重命名或移动到另一个文件夹，目标的父文件夹需要存在
+    ///
+    /// 目标已经存在时不会覆盖，返回的 exist 为 true
+    pub async fn rename(&self, from: &str, to: &str) -> crate::Result<AdriveOpenFileMove> {
+        let file_id = self.resolve(from).await?;
+        let (to_parent, to_name) = split_path(to);
+        let to_name = if let Some(to_name) = to_name {
+            to_name
+        } else {
+            return Err(AlipanError::msg("can't rename to the root folder"));
+        };
+        let to_parent_file_id = self.resolve(to_parent.as_str()).await?;
+        self.client
+            .adrive_open_file_move()
+            .await
+            .drive_id(self.drive_id.as_str())
+            .file_id(file_id)
+            .to_parent_file_id(to_parent_file_id)
+            .check_name_mode(CheckNameMode::Refuse)
+            .new_name(to_name)
+            .request()
+            .await
+    }
+
+    /// 复制到 to_folder 文件夹下，名称保持不变
+    pub async fn copy(&self, from: &str, to_folder: &str) -> crate::Result<AdriveOpenFileCopy> {
+        let file_id = self.resolve(from).await?;
+        let to_parent_file_id = self.resolve(to_folder).await?;
+        self.client
+            .adrive_open_file_copy()
+            .await
+            .drive_id(self.drive_id.as_str())
+            .file_id(file_id)
+            .to_parent_file_id(to_parent_file_id)
+            .request()
+            .await
+    }
+
+    /// 路径对应的 file_id，根目录为 root，客户端设置了 AdrivePathCache 时优先使用缓存
+    pub async fn resolve(&self, path: &str) -> crate::Result<String> {
+        if path_components(path).is_empty() {
+            return Ok("root".to_string());
+        }
+        if let Some(path_cache) = self.client.clone_path_cache().await {
+            if let Some(file_id) = path_cache.file_id(self.drive_id.as_str(), path) {
+                return Ok(file_id);
+            }
+        }
+        Ok(self.stat(path).await?.file_id)
+    }
+}
+
+fn path_components(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|name| !name.is_empty() && *name != ".")
+        .collect()
+}
+
+/// 去掉多余的 `/` 和 `.`，总是以 `/` 开头
+pub(crate) fn normalize_path(path: &str) -> String {
+    format!("/{}", path_components(path).join("/"))
+}
+
+/// 拆分为父文件夹和名称，根目录没有名称
+fn split_path(path: &str) -> (String, Option<String>) {
+    let mut components = path_components(path);
+    let name = components.pop().map(str::to_string);
+    (format!("/{}", components.join("/")), name)
+}
+
+fn not_found(err: AlipanError, path: &str) -> AlipanError {
+    match &err.inner {
+        ErrorInfo::ServerError(server_error) if server_error.code.starts_with("NotFound") => {
+            AlipanError::new(ErrorInfo::PathNotFound(path.to_string()))
+        }
+        _ => err,
+    }
+}
@@ -0,0 +1,5 @@
+pub mod adrive_fs;
+pub mod path_cache;
+
+pub use adrive_fs::*;
+pub use path_cache::*;
@@ -0,0 +1,205 @@
+use crate::adrive_fs::normalize_path;
+use crate::AdriveOpenFile;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 路径与 file_id 的双向缓存，clone 之后共享同一份数据
+///
+/// 设置到 AdriveClient 之后，list / get / get_by_path / create 的结果会写入缓存，
+/// 通过同一个客户端 move / update / trash / delete 时会删除对应的路径以及下级路径
+#[derive(Debug, Clone)]
+pub struct AdrivePathCache {
+    ttl: Duration,
+    max_entries: Option<usize>,
+    inner: Arc<std::sync::Mutex<PathCacheInner>>,
+}
+
+#[derive(Debug, Default)]
+struct PathCacheInner {
+    /// (drive_id, path) -> (file_id, 过期时间)
+    paths: HashMap<(String, String), (String, Instant)>,
+    /// (drive_id, file_id) -> path
+    file_ids: HashMap<(String, String), String>,
+    /// 按写入顺序排列，用于淘汰，可能包含已经被覆盖或删除的记录
+    order: VecDeque<((String, String), Instant)>,
+}
+
+impl AdrivePathCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: None,
+            inner: Arc::new(std::sync::Mutex::new(PathCacheInner::default())),
+        }
+    }
+
+    /// 最多缓存的路径数，超出时淘汰最早写入的
+    pub fn max_entries(mut self, max_entries: impl Into<Option<usize>>) -> Self {
+        self.max_entries = max_entries.into();
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 路径对应的 file_id，根目录总是 root
+    pub fn file_id(&self, drive_id: &str, path: &str) -> Option<String> {
+        let path = normalize_path(path);
+        if path == "/" {
+            return Some("root".to_string());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let key = (drive_id.to_string(), path);
+        match inner.paths.get(&key) {
+            Some((file_id, expires_at)) if *expires_at > Instant::now() => Some(file_id.clone()),
+            Some(_) => {
+                inner.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// file_id 对应的路径
+    pub fn path(&self, drive_id: &str, file_id: &str) -> Option<String> {
+        if file_id == "root" {
+            return Some("/".to_string());
+        }
+        let path = self
+            .inner
+            .lock()
+            .unwrap()
+            .file_ids
+            .get(&(drive_id.to_string(), file_id.to_string()))
+            .cloned()?;
+        // 同时检查是否过期
+        match self.file_id(drive_id, path.as_str()) {
+            Some(cached) if cached == file_id => Some(path),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, drive_id: &str, path: &str, file_id: &str) {
+        let path = normalize_path(path);
+        if path == "/" {
+            return;
+        }
+        let expires_at = Instant::now() + self.ttl;
+        let mut inner = self.inner.lock().unwrap();
+        let file_key = (drive_id.to_string(), file_id.to_string());
+        // 文件已经不在原来的位置，原来的下级路径也不再可信
+        if let Some(old_path) = inner.file_ids.get(&file_key).cloned() {
+            if old_path != path {
+                inner.remove_tree(drive_id, old_path.as_str());
+            }
+        }
+        let key = (drive_id.to_string(), path.clone());
+        if let Some((old_file_id, _)) = inner.paths.get(&key) {
+            if old_file_id != file_id {
+                inner.remove_tree(drive_id, path.as_str());
+            }
+        }
+        inner
+            .paths
+            .insert(key.clone(), (file_id.to_string(), expires_at));
+        inner.file_ids.insert(file_key, path);
+        inner.order.push_back((key, expires_at));
+        inner.evict(self.max_entries);
+    }
+
+    /// 父文件夹的路径已经缓存时，记录它下面的一项
+    pub fn insert_child(&self, drive_id: &str, parent_file_id: &str, name: &str, file_id: &str) {
+        if let Some(parent_path) = self.path(drive_id, parent_file_id) {
+            let path = format!("{}/{}", parent_path.trim_end_matches('/'), name);
+            self.insert(drive_id, path.as_str(), file_id);
+        }
+    }
+
+    pub fn insert_file(&self, file: &AdriveOpenFile) {
+        self.insert_child(
+            file.drive_id.as_str(),
+            file.parent_file_id.as_str(),
+            file.name.as_str(),
+            file.file_id.as_str(),
+        );
+    }
+
+    /// 删除文件以及它下级的全部路径，不知道文件的路径时清空这个 drive 的缓存
+    pub fn invalidate(&self, drive_id: &str, file_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let path = inner
+            .file_ids
+            .get(&(drive_id.to_string(), file_id.to_string()))
+            .cloned();
+        match path {
+            Some(path) => inner.remove_tree(drive_id, path.as_str()),
+            None => inner.remove_tree(drive_id, "/"),
+        }
+    }
+
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap() = PathCacheInner::default();
+    }
+
+    /// 缓存的路径数，包含已经过期但还没有清理的
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl PathCacheInner {
+    fn remove(&mut self, key: &(String, String)) {
+        if let Some((file_id, _)) = self.paths.remove(key) {
+            let file_key = (key.0.clone(), file_id);
+            if self.file_ids.get(&file_key) == Some(&key.1) {
+                self.file_ids.remove(&file_key);
+            }
+        }
+    }
+
+    fn remove_tree(&mut self, drive_id: &str, path: &str) {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        let keys = self
+            .paths
+            .keys()
+            .filter(|(key_drive_id, key_path)| {
+                key_drive_id == drive_id && (key_path == path || key_path.starts_with(&prefix))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    fn evict(&mut self, max_entries: Option<usize>) {
+        let now = Instant::now();
+        while let Some((key, expires_at)) = self.order.front().cloned() {
+            let over = max_entries.is_some_and(|max| self.paths.len() > max);
+            if !over && expires_at > now {
+                break;
+            }
+            self.order.pop_front();
+            // 只删除仍然是这次写入的记录
+            if self
+                .paths
+                .get(&key)
+                .is_some_and(|(_, at)| *at == expires_at)
+            {
+                self.remove(&key);
+            }
+        }
+        // 反复写入同一个路径时 order 会积累无效的记录
+        if self.order.len() > self.paths.len() * 2 + 64 {
+            let paths = &self.paths;
+            self.order
+                .retain(|(key, expires_at)| paths.get(key).is_some_and(|(_, at)| at == expires_at));
+        }
+    }
+}
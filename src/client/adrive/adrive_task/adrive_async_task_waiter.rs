@@ -0,0 +1,87 @@
+use crate::{AdriveAsyncTaskState, AdriveClient, AlipanError, ErrorInfo};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 可能返回 async_task_id 的操作结果，例如复制、移动、删除、移到回收站
+pub trait AdriveAsyncTask {
+    /// 操作同步完成时为 None
+    fn async_task_id(&self) -> Option<&str>;
+}
+
+impl AdriveClient {
+    pub async fn adrive_async_task_waiter(&self) -> AdriveAsyncTaskWaiter<'_> {
+        AdriveAsyncTaskWaiter {
+            client: self,
+            initial_interval: Duration::from_millis(500),
+            max_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+/// 轮询异步任务直到完成，查询间隔从 initial_interval 开始每次翻倍，不超过 max_interval
+#[derive(Debug)]
+pub struct AdriveAsyncTaskWaiter<'a> {
+    pub client: &'a AdriveClient,
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    /// 超过这个时间任务仍未完成时返回 AsyncTaskTimeout
+    pub timeout: Duration,
+}
+
+impl<'a> AdriveAsyncTaskWaiter<'a> {
+    pub fn initial_interval(mut self, initial_interval: Duration) -> Self {
+        self.initial_interval = initial_interval;
+        self
+    }
+
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = max_interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 等待操作对应的异步任务完成，没有 async_task_id 时直接返回
+    pub async fn wait_until_done(&self, task: &impl AdriveAsyncTask) -> crate::Result<()> {
+        match task.async_task_id() {
+            Some(async_task_id) => self.wait_async_task_id(async_task_id).await,
+            None => Ok(()),
+        }
+    }
+
+    pub async fn wait_async_task_id(&self, async_task_id: &str) -> crate::Result<()> {
+        let deadline = Instant::now() + self.timeout;
+        let mut interval = self.initial_interval;
+        loop {
+            let task = self
+                .client
+                .adrive_open_file_async_task_get()
+                .await
+                .async_task_id(async_task_id)
+                .request()
+                .await?;
+            match task.state {
+                AdriveAsyncTaskState::Succeed => return Ok(()),
+                AdriveAsyncTaskState::Failed => {
+                    return Err(AlipanError::new(ErrorInfo::AsyncTaskFailed(
+                        async_task_id.to_string(),
+                    )))
+                }
+                AdriveAsyncTaskState::Running => {}
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(AlipanError::new(ErrorInfo::AsyncTaskTimeout(
+                    async_task_id.to_string(),
+                )));
+            }
+            // 最后一次查询不晚于 deadline
+            tokio::time::sleep_until(deadline.min(now + interval)).await;
+            interval = (interval * 2).min(self.max_interval);
+        }
+    }
+}
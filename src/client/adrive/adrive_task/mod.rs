@@ -0,0 +1,3 @@
+pub mod adrive_async_task_waiter;
+
+pub use adrive_async_task_waiter::*;
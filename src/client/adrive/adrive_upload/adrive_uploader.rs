@@ -0,0 +1,761 @@
+use crate::{
+    adrive_source_content_hash, adrive_source_pre_hash, adrive_source_proof_code,
+    adrive_upload_session_key, cancellable, progress_stream, rate_limit_stream, AdriveClient,
+    AdriveOpenFileComplete, AdriveOpenFileCreate, AdriveOpenFileCreateRequest,
+    AdriveOpenFilePartInfoCreate, AdriveOpenFileType, AdriveUploadBody, AdriveUploadSession,
+    AlipanError, BoxedAdriveUploadSessionStore, BoxedAdriveUploadSource, BoxedTransferProgress,
+    BytesUploadSource, CancellationToken, CheckNameMode, ErrorInfo, FileUploadSource, OptionParam,
+    RateLimiter, ReaderUploadSource, ADRIVE_CONTENT_HASH_NAME, ADRIVE_PROOF_VERSION,
+};
+use futures::future::Either;
+use futures::stream::FuturesUnordered;
+use futures::{Future, StreamExt, TryStreamExt};
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Body, StatusCode};
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek};
+
+/// 分片最小 100KB
+pub const ADRIVE_UPLOAD_MIN_PART_SIZE: i64 = 100 << 10;
+/// 分片最大 5GB
+pub const ADRIVE_UPLOAD_MAX_PART_SIZE: i64 = 5 << 30;
+/// 单个文件最多 10000 个分片
+pub const ADRIVE_UPLOAD_MAX_PART_COUNT: i64 = 10000;
+/// 默认分片 16MB
+pub const ADRIVE_UPLOAD_DEFAULT_PART_SIZE: i64 = 16 << 20;
+/// 每次获取上传地址的分片数
+pub const ADRIVE_UPLOAD_URL_BATCH_SIZE: usize = 16;
+
+impl AdriveClient {
+    pub async fn adrive_uploader(&self) -> AdriveUploader<'_> {
+        AdriveUploader {
+            client: self,
+            drive_id: None.into(),
+            parent_file_id: "root".to_string(),
+            name: None.into(),
+            check_name_mode: CheckNameMode::Refuse,
+            source: None.into(),
+            part_size: None.into(),
+            rapid_upload: true,
+            concurrency: 1,
+            retry_times: 3,
+            session_store: None.into(),
+            progress: None.into(),
+            cancellation_token: None.into(),
+            rate_limiter: None.into(),
+        }
+    }
+}
+
+/// upload_with_outcome 的结果
+#[derive(Debug, Clone)]
+pub struct AdriveUploadOutcome {
+    pub complete: AdriveOpenFileComplete,
+    /// 服务端已有相同内容的文件，没有上传分片
+    pub rapid_upload: bool,
+}
+
+/// 上传文件：创建文件、上传分片、完成上传
+#[derive(Debug)]
+pub struct AdriveUploader<'a> {
+    pub client: &'a AdriveClient,
+    pub drive_id: OptionParam<String>,
+    pub parent_file_id: String,
+    /// 不设置时使用本地文件名
+    pub name: OptionParam<String>,
+    pub check_name_mode: CheckNameMode,
+    pub source: OptionParam<Arc<BoxedAdriveUploadSource>>,
+    /// 期望的分片大小，会被调整到 100KB~5GB 之间，并保证分片数不超过 10000
+    pub part_size: OptionParam<i64>,
+    /// 尝试秒传，先检查 pre_hash，匹配时再计算 sha1 和 proof_code
+    pub rapid_upload: bool,
+    /// 同时上传的分片数，reader 来源的分片读取仍然是串行的
+    pub concurrency: usize,
+    /// 单个分片失败后的重试次数
+    pub retry_times: usize,
+    /// 保存上传会话，中断后再次上传同一个文件时只上传剩余的分片
+    pub session_store: OptionParam<Arc<BoxedAdriveUploadSessionStore>>,
+    /// 上传进度回调
+    pub progress: OptionParam<Arc<BoxedTransferProgress>>,
+    /// 取消后会中断正在上传的分片并返回 ErrorInfo::Cancelled，已保存的上传会话可以继续上传
+    pub cancellation_token: OptionParam<CancellationToken>,
+    /// 只限制这次上传的速度，不设置时使用 AdriveClient 的限速
+    pub rate_limiter: OptionParam<RateLimiter>,
+}
+
+impl<'a> AdriveUploader<'a> {
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn parent_file_id(mut self, parent_file_id: impl Into<String>) -> Self {
+        self.parent_file_id = parent_file_id.into();
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<OptionParam<String>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn check_name_mode(mut self, check_name_mode: CheckNameMode) -> Self {
+        self.check_name_mode = check_name_mode;
+        self
+    }
+
+    pub fn source(mut self, source: BoxedAdriveUploadSource) -> Self {
+        self.source = Arc::new(source).into();
+        self
+    }
+
+    pub fn file_path(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if self.name.is_none() {
+            if let Some(name) = path.file_name() {
+                self.name = name.to_string_lossy().to_string().into();
+            }
+        }
+        self.source(Box::new(FileUploadSource::new(path)))
+    }
+
+    pub fn reader<R>(self, reader: R) -> Self
+    where
+        R: AsyncRead + AsyncSeek + Unpin + Send + 'static,
+    {
+        self.source(Box::new(ReaderUploadSource::new(reader)))
+    }
+
+    pub fn part_size(mut self, part_size: impl Into<OptionParam<i64>>) -> Self {
+        self.part_size = part_size.into();
+        self
+    }
+
+    pub fn rapid_upload(mut self, rapid_upload: bool) -> Self {
+        self.rapid_upload = rapid_upload;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn retry_times(mut self, retry_times: usize) -> Self {
+        self.retry_times = retry_times;
+        self
+    }
+
+    pub fn session_store(
+        mut self,
+        session_store: impl Into<OptionParam<Arc<BoxedAdriveUploadSessionStore>>>,
+    ) -> Self {
+        self.session_store = session_store.into();
+        self
+    }
+
+    pub fn progress(
+        mut self,
+        progress: impl Into<OptionParam<Arc<BoxedTransferProgress>>>,
+    ) -> Self {
+        self.progress = progress.into();
+        self
+    }
+
+    pub fn cancellation_token(
+        mut self,
+        cancellation_token: impl Into<OptionParam<CancellationToken>>,
+    ) -> Self {
+        self.cancellation_token = cancellation_token.into();
+        self
+    }
+
+    pub fn rate_limiter(mut self, rate_limiter: impl Into<OptionParam<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter.into();
+        self
+    }
+}
+
+impl<'a> AdriveUploader<'a> {
+    pub async fn upload(&self) -> crate::Result<AdriveOpenFileComplete> {
+        Ok(self.upload_with_outcome().await?.complete)
+    }
+
+    /// 与 upload 相同，同时返回是否秒传
+    pub async fn upload_with_outcome(&self) -> crate::Result<AdriveUploadOutcome> {
+        cancellable(self.cancellation_token.as_ref(), self.upload_source()).await
+    }
+
+    /// 上传长度未知的数据流：按分片大小读取，边读边申请上传地址，读到 EOF 时完成上传
+    ///
+    /// 不计算哈希，所以不会秒传；分片数最多 10000，需要根据数据量设置 part_size
+    pub async fn upload_stream<R>(&self, reader: R) -> crate::Result<AdriveOpenFileComplete>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        cancellable(self.cancellation_token.as_ref(), self.upload_reader(reader)).await
+    }
+
+    async fn upload_source(&self) -> crate::Result<AdriveUploadOutcome> {
+        let drive_id = if let Some(drive_id) = self.drive_id.deref() {
+            drive_id.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("drive_id"));
+        };
+        let name = if let Some(name) = self.name.deref() {
+            name.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("name"));
+        };
+        let source = if let Some(source) = self.source.deref() {
+            source.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("source"));
+        };
+        let size = source.size().await?;
+        let session_key = adrive_upload_session_key(&drive_id, &self.parent_file_id, &name);
+        // 读取或保存会话都需要指纹，可能要完整读取一遍数据，所以只计算一次
+        let fingerprint = match self.session_store.deref() {
+            Some(_) => source.fingerprint().await?,
+            None => None,
+        };
+        if let Some(session_store) = self.session_store.deref() {
+            if let Some(session) = session_store.get_session(&session_key).await? {
+                if session.size == size && fingerprint.as_ref() == Some(&session.fingerprint) {
+                    if let Some(parts) = self.remaining_parts(&session).await {
+                        self.on_start(Some(
+                            parts
+                                .iter()
+                                .map(|(part_number, _)| part_len(&session, *part_number))
+                                .sum(),
+                        ));
+                        self.upload_parts(source.as_ref(), &session, parts).await?;
+                        let complete = self.complete(&session).await?;
+                        session_store.remove_session(&session_key).await?;
+                        return Ok(AdriveUploadOutcome {
+                            complete,
+                            rapid_upload: false,
+                        });
+                    }
+                }
+                session_store.remove_session(&session_key).await?;
+            }
+        }
+        let part_size = adrive_upload_part_size(
+            size,
+            self.part_size.unwrap_or(ADRIVE_UPLOAD_DEFAULT_PART_SIZE),
+        )?;
+        let part_info_list = (1..=adrive_upload_part_count(size, part_size))
+            .map(|part_number| AdriveOpenFilePartInfoCreate { part_number })
+            .collect::<Vec<_>>();
+        let create = if self.rapid_upload {
+            self.rapid_create(source.as_ref(), &drive_id, &name, size, part_info_list)
+                .await?
+        } else {
+            self.create_request(&drive_id, &name, size, part_info_list)
+                .await
+                .request()
+                .await?
+        };
+        if create.exist {
+            return Err(AlipanError::new(ErrorInfo::FileExist(Box::new(create))));
+        }
+        let upload_id = if let Some(upload_id) = &create.upload_id {
+            upload_id.clone()
+        } else {
+            return Err(AlipanError::msg("upload_id not found"));
+        };
+        let session = AdriveUploadSession {
+            drive_id: create.drive_id.clone(),
+            file_id: create.file_id.clone(),
+            upload_id,
+            size,
+            part_size,
+            fingerprint: "".to_string(),
+        };
+        if create.rapid_upload {
+            self.on_start(Some(0));
+            return Ok(AdriveUploadOutcome {
+                complete: self.complete(&session).await?,
+                rapid_upload: true,
+            });
+        }
+        self.on_start(Some(size));
+        if let Some(session_store) = self.session_store.deref() {
+            // 没有指纹的数据无法判断是否变化，不保存会话
+            if let Some(fingerprint) = fingerprint {
+                let session = AdriveUploadSession {
+                    fingerprint,
+                    ..session.clone()
+                };
+                session_store.set_session(&session_key, session).await?;
+            }
+        }
+        let parts = create
+            .part_info_list
+            .into_iter()
+            .map(|part| (part.part_number, part.upload_url))
+            .collect::<Vec<_>>();
+        self.upload_parts(source.as_ref(), &session, parts).await?;
+        let complete = self.complete(&session).await?;
+        if let Some(session_store) = self.session_store.deref() {
+            session_store.remove_session(&session_key).await?;
+        }
+        Ok(AdriveUploadOutcome {
+            complete,
+            rapid_upload: false,
+        })
+    }
+
+    async fn upload_reader<R>(&self, reader: R) -> crate::Result<AdriveOpenFileComplete>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let drive_id = if let Some(drive_id) = self.drive_id.deref() {
+            drive_id.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("drive_id"));
+        };
+        let name = if let Some(name) = self.name.deref() {
+            name.clone()
+        } else {
+            return Err(crate::Error::require_param_missing("name"));
+        };
+        let part_size = self
+            .part_size
+            .unwrap_or(ADRIVE_UPLOAD_DEFAULT_PART_SIZE)
+            .clamp(ADRIVE_UPLOAD_MIN_PART_SIZE, ADRIVE_UPLOAD_MAX_PART_SIZE);
+        let create = self
+            .client
+            .adrive_open_file_create()
+            .await
+            .drive_id(drive_id)
+            .parent_file_id(self.parent_file_id.as_str())
+            .name(name)
+            .r#type(AdriveOpenFileType::File)
+            .check_name_mode(self.check_name_mode)
+            .part_info_list(vec![AdriveOpenFilePartInfoCreate { part_number: 1 }])
+            .request()
+            .await?;
+        if create.exist {
+            return Err(AlipanError::new(ErrorInfo::FileExist(Box::new(create))));
+        }
+        let session = AdriveUploadSession {
+            drive_id: create.drive_id.clone(),
+            file_id: create.file_id.clone(),
+            upload_id: if let Some(upload_id) = &create.upload_id {
+                upload_id.clone()
+            } else {
+                return Err(AlipanError::msg("upload_id not found"));
+            },
+            size: 0,
+            part_size,
+            fingerprint: "".to_string(),
+        };
+        self.on_start(None);
+        let agent = self.client.clone_agent().await;
+        let mut upload_urls = create
+            .part_info_list
+            .into_iter()
+            .map(|part| (part.part_number, part.upload_url))
+            .collect::<HashMap<_, _>>();
+        let concurrency = self.concurrency.max(1);
+        let mut reader = reader;
+        let mut uploading = FuturesUnordered::new();
+        for part_number in 1.. {
+            // 同时最多 concurrency 个分片在上传，也限制了缓存在内存中的分片数量
+            if uploading.len() >= concurrency {
+                uploading.try_next().await?;
+            }
+            // 读取下一个分片时继续推进正在上传的分片，缓冲区随读到的数据增长
+            let mut buffer = Vec::new();
+            drive_uploads(&mut uploading, async {
+                (&mut reader)
+                    .take(part_size as u64)
+                    .read_to_end(&mut buffer)
+                    .await?;
+                crate::Result::Ok(())
+            })
+            .await?;
+            if buffer.is_empty() && part_number > 1 {
+                break;
+            }
+            if part_number > ADRIVE_UPLOAD_MAX_PART_COUNT {
+                return Err(AlipanError::msg("too many parts, increase part_size"));
+            }
+            let upload_url = match upload_urls.remove(&part_number) {
+                Some(upload_url) if !upload_url.is_empty() => upload_url,
+                _ => {
+                    // 提前申请一批后续分片的上传地址
+                    let batch = (part_number..part_number + ADRIVE_UPLOAD_URL_BATCH_SIZE as i64)
+                        .map(|part_number| (part_number, "".to_string()))
+                        .collect();
+                    let mut batch =
+                        drive_uploads(&mut uploading, self.fill_upload_urls(&session, batch))
+                            .await?
+                            .into_iter();
+                    let (_, upload_url) = batch.next().unwrap_or_default();
+                    upload_urls.extend(batch);
+                    upload_url
+                }
+            };
+            let part = UploadPart {
+                part_number,
+                offset: 0,
+                len: buffer.len() as i64,
+                upload_url,
+            };
+            let source: BoxedAdriveUploadSource = Box::new(BytesUploadSource::new(buffer));
+            let agent = &agent;
+            let session = &session;
+            uploading.push(async move { self.upload_part(agent, &source, session, part).await });
+        }
+        while uploading.try_next().await?.is_some() {}
+        self.complete(&session).await
+    }
+
+    async fn create_request(
+        &self,
+        drive_id: &str,
+        name: &str,
+        size: i64,
+        part_info_list: Vec<AdriveOpenFilePartInfoCreate>,
+    ) -> AdriveOpenFileCreateRequest {
+        self.client
+            .adrive_open_file_create()
+            .await
+            .drive_id(drive_id)
+            .parent_file_id(self.parent_file_id.as_str())
+            .name(name)
+            .r#type(AdriveOpenFileType::File)
+            .check_name_mode(self.check_name_mode)
+            .size(size)
+            .part_info_list(part_info_list)
+    }
+
+    /// 秒传：先用 pre_hash 检查，服务端返回 PreHashMatched 时再计算完整的 sha1 和 proof_code
+    async fn rapid_create(
+        &self,
+        source: &BoxedAdriveUploadSource,
+        drive_id: &str,
+        name: &str,
+        size: i64,
+        part_info_list: Vec<AdriveOpenFilePartInfoCreate>,
+    ) -> crate::Result<AdriveOpenFileCreate> {
+        let pre_hash = adrive_source_pre_hash(source).await?;
+        let create = self
+            .create_request(drive_id, name, size, part_info_list.clone())
+            .await
+            .pre_hash(pre_hash)
+            .request()
+            .await;
+        match create {
+            Err(AlipanError {
+                inner: ErrorInfo::ServerError(ref server_error),
+                ..
+            }) if server_error.code == "PreHashMatched" => {}
+            create => return create,
+        }
+        let access_token = self
+            .client
+            .clone_access_token_loader()
+            .await
+            .get_access_token()
+            .await?
+            .access_token;
+        self.create_request(drive_id, name, size, part_info_list)
+            .await
+            .content_hash_name(ADRIVE_CONTENT_HASH_NAME)
+            .content_hash(adrive_source_content_hash(source).await?)
+            .proof_code(adrive_source_proof_code(access_token.as_str(), source).await?)
+            .proof_version(ADRIVE_PROOF_VERSION)
+            .request()
+            .await
+    }
+
+    async fn complete(
+        &self,
+        session: &AdriveUploadSession,
+    ) -> crate::Result<AdriveOpenFileComplete> {
+        self.client
+            .adrive_open_file_complete()
+            .await
+            .drive_id(session.drive_id.as_str())
+            .file_id(session.file_id.as_str())
+            .upload_id(session.upload_id.as_str())
+            .request()
+            .await
+    }
+
+    /// 续传时查询服务端已有的分片，查询失败（例如 upload_id 已失效）时返回 None 重新上传
+    async fn remaining_parts(&self, session: &AdriveUploadSession) -> Option<Vec<(i64, String)>> {
+        match self.uploaded_part_numbers(session).await {
+            Ok(uploaded) => Some(
+                (1..=adrive_upload_part_count(session.size, session.part_size))
+                    .filter(|part_number| !uploaded.contains(part_number))
+                    .map(|part_number| (part_number, "".to_string()))
+                    .collect(),
+            ),
+            Err(err) => {
+                tracing::warn!("list uploaded parts failed, upload again: {}", err);
+                None
+            }
+        }
+    }
+
+    async fn uploaded_part_numbers(
+        &self,
+        session: &AdriveUploadSession,
+    ) -> crate::Result<HashSet<i64>> {
+        self.client
+            .adrive_open_file_list_uploaded_parts()
+            .await
+            .drive_id(session.drive_id.as_str())
+            .file_id(session.file_id.as_str())
+            .upload_id(session.upload_id.as_str())
+            .into_stream()
+            .map_ok(|part| part.part_number)
+            .try_collect()
+            .await
+    }
+
+    /// 并发上传分片，parts 为 (part_number, upload_url)，upload_url 为空时按批次获取
+    async fn upload_parts(
+        &self,
+        source: &BoxedAdriveUploadSource,
+        target: &AdriveUploadSession,
+        parts: Vec<(i64, String)>,
+    ) -> crate::Result<()> {
+        let agent = self.client.clone_agent().await;
+        let batch_size = ADRIVE_UPLOAD_URL_BATCH_SIZE.max(self.concurrency);
+        let batches = parts
+            .chunks(batch_size)
+            .map(|batch| batch.to_vec())
+            .collect::<Vec<_>>();
+        futures::stream::iter(batches)
+            .then(|batch| self.fill_upload_urls(target, batch))
+            .map_ok(|batch| futures::stream::iter(batch.into_iter().map(Ok)))
+            .try_flatten()
+            .map_ok(|(part_number, upload_url)| {
+                let part = UploadPart {
+                    part_number,
+                    offset: (part_number - 1) * target.part_size,
+                    len: part_len(target, part_number),
+                    upload_url,
+                };
+                self.upload_part(&agent, source, target, part)
+            })
+            .try_buffer_unordered(self.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(())
+    }
+
+    /// 一次请求获取一批分片的上传地址
+    async fn fill_upload_urls(
+        &self,
+        target: &AdriveUploadSession,
+        batch: Vec<(i64, String)>,
+    ) -> crate::Result<Vec<(i64, String)>> {
+        let missing = batch
+            .iter()
+            .filter(|(_, upload_url)| upload_url.is_empty())
+            .map(|(part_number, _)| AdriveOpenFilePartInfoCreate {
+                part_number: *part_number,
+            })
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(batch);
+        }
+        let upload_urls = self
+            .client
+            .adrive_open_file_get_upload_url()
+            .await
+            .drive_id(target.drive_id.as_str())
+            .file_id(target.file_id.as_str())
+            .upload_id(target.upload_id.as_str())
+            .part_info_list(missing)
+            .request()
+            .await?
+            .part_info_list
+            .into_iter()
+            .map(|part| (part.part_number, part.upload_url))
+            .collect::<HashMap<_, _>>();
+        batch
+            .into_iter()
+            .map(|(part_number, upload_url)| {
+                if !upload_url.is_empty() {
+                    Ok((part_number, upload_url))
+                } else if let Some(upload_url) = upload_urls.get(&part_number) {
+                    Ok((part_number, upload_url.clone()))
+                } else {
+                    Err(AlipanError::msg(format!(
+                        "upload_url not found: part_number {}",
+                        part_number
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    /// 上传单个分片，失败时只重试这个分片，上传地址过期时重新获取
+    async fn upload_part(
+        &self,
+        agent: &reqwest::Client,
+        source: &BoxedAdriveUploadSource,
+        target: &AdriveUploadSession,
+        part: UploadPart,
+    ) -> crate::Result<()> {
+        let UploadPart {
+            part_number,
+            offset,
+            len,
+            mut upload_url,
+        } = part;
+        let rate_limiter = match self.rate_limiter.deref() {
+            Some(rate_limiter) => Some(rate_limiter.clone()),
+            None => self.client.clone_rate_limiter().await,
+        };
+        let mut retry = 0;
+        let mut reissue = 0;
+        loop {
+            let body =
+                rate_limit_stream(source.read_range(offset, len).await?, rate_limiter.clone());
+            let sent = Arc::new(AtomicU64::new(0));
+            let body = progress_stream(body, self.progress.deref().clone(), sent.clone());
+            let err = match put_part(agent, part_number, upload_url.as_str(), Box::pin(body), len)
+                .await
+            {
+                Ok(()) => {
+                    if let Some(progress) = self.progress.deref() {
+                        progress.on_part_completed(part_number, len);
+                    }
+                    return Ok(());
+                }
+                Err(err) => err,
+            };
+            match err {
+                AlipanError {
+                    inner: ErrorInfo::UploadUrlExpired(_),
+                    ..
+                } if reissue < self.retry_times => {
+                    reissue += 1;
+                    tracing::debug!("upload url of part {} expired, reissue", part_number);
+                    self.on_retry(part_number, reissue, sent.load(Ordering::Relaxed), &err);
+                    upload_url = self
+                        .fill_upload_urls(target, vec![(part_number, "".to_string())])
+                        .await?
+                        .remove(0)
+                        .1;
+                }
+                err if retry < self.retry_times => {
+                    retry += 1;
+                    self.on_retry(part_number, retry, sent.load(Ordering::Relaxed), &err);
+                    tracing::warn!(
+                        "upload part {} failed, retry {}/{}: {}",
+                        part_number,
+                        retry,
+                        self.retry_times,
+                        err
+                    );
+                    tokio::time::sleep(Duration::from_secs(retry as u64)).await;
+                }
+                err => return Err(err),
+            }
+        }
+    }
+
+    fn on_start(&self, total: Option<i64>) {
+        if let Some(progress) = self.progress.deref() {
+            progress.on_start(total);
+        }
+    }
+
+    fn on_retry(&self, part_number: i64, retry: usize, discarded: u64, err: &AlipanError) {
+        if let Some(progress) = self.progress.deref() {
+            progress.on_retry(part_number, retry, discarded, err);
+        }
+    }
+}
+
+fn part_len(session: &AdriveUploadSession, part_number: i64) -> i64 {
+    let offset = (part_number - 1) * session.part_size;
+    session.part_size.min(session.size - offset)
+}
+
+struct UploadPart {
+    part_number: i64,
+    offset: i64,
+    len: i64,
+    upload_url: String,
+}
+
+async fn put_part(
+    agent: &reqwest::Client,
+    part_number: i64,
+    url: &str,
+    body: AdriveUploadBody,
+    len: i64,
+) -> crate::Result<()> {
+    let resp = agent
+        .put(url)
+        .header(CONTENT_LENGTH, len)
+        .body(Body::wrap_stream(body))
+        .send()
+        .await?;
+    let code = resp.status();
+    if !code.is_success() {
+        let text = resp.text().await?;
+        // 过期的上传地址返回 403：<Code>AccessDenied</Code><Message>Request has expired.</Message>
+        if code == StatusCode::FORBIDDEN && text.contains("expired") {
+            return Err(AlipanError::new(ErrorInfo::UploadUrlExpired(part_number)));
+        }
+        return Err(AlipanError::server(code, text.as_str()));
+    }
+    Ok(())
+}
+
+/// 根据文件大小和期望的分片大小，计算实际使用的分片大小
+pub fn adrive_upload_part_size(size: i64, part_size: i64) -> crate::Result<i64> {
+    let min_part_size = (size + ADRIVE_UPLOAD_MAX_PART_COUNT - 1) / ADRIVE_UPLOAD_MAX_PART_COUNT;
+    let part_size = part_size
+        .min(ADRIVE_UPLOAD_MAX_PART_SIZE)
+        .max(min_part_size)
+        .max(ADRIVE_UPLOAD_MIN_PART_SIZE);
+    if part_size > ADRIVE_UPLOAD_MAX_PART_SIZE {
+        return Err(AlipanError::msg(format!("file too large: {}", size)));
+    }
+    Ok(part_size)
+}
+
+/// 分片数量，空文件也需要一个分片
+pub fn adrive_upload_part_count(size: i64, part_size: i64) -> i64 {
+    ((size + part_size - 1) / part_size).max(1)
+}
+
+/// 等待 future 完成，同时推进正在上传的分片，避免已经开始的上传因为没有被 poll 而超时
+async fn drive_uploads<T, U>(
+    uploading: &mut FuturesUnordered<U>,
+    future: impl Future<Output = crate::Result<T>>,
+) -> crate::Result<T>
+where
+    U: Future<Output = crate::Result<()>>,
+{
+    let mut future = std::pin::pin!(future);
+    loop {
+        if uploading.is_empty() {
+            return future.await;
+        }
+        match futures::future::select(future.as_mut(), uploading.try_next()).await {
+            Either::Left((result, _)) => return result,
+            Either::Right((result, _)) => {
+                result?;
+            }
+        }
+    }
+}
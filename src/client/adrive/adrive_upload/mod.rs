@@ -0,0 +1,9 @@
+pub mod adrive_uploader;
+pub mod rapid_upload;
+pub mod upload_session;
+pub mod upload_source;
+
+pub use adrive_uploader::*;
+pub use rapid_upload::*;
+pub use upload_session::*;
+pub use upload_source::*;
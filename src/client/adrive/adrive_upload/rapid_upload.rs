@@ -0,0 +1,83 @@
+use crate::{AdriveUploadSource, BoxedAdriveUploadSource};
+use base64::Engine;
+use futures::StreamExt;
+
+/// 预秒传只计算文件前 1KB 的 sha1
+pub const ADRIVE_PRE_HASH_SIZE: i64 = 1024;
+pub const ADRIVE_CONTENT_HASH_NAME: &str = "sha1";
+pub const ADRIVE_PROOF_VERSION: &str = "v1";
+
+/// pre_hash: 前 1KB 数据的 sha1
+pub fn adrive_pre_hash(data: &[u8]) -> String {
+    sha1_hex(&data[..data.len().min(ADRIVE_PRE_HASH_SIZE as usize)])
+}
+
+/// content_hash: 全部数据的 sha1
+pub fn adrive_content_hash(data: &[u8]) -> String {
+    sha1_hex(data)
+}
+
+/// proof_code 取数据的区间 [start, end)
+///
+/// md5(access_token) 的前 16 位十六进制数对文件大小取模作为开始位置，最多取 8 个字节
+pub fn adrive_proof_range(access_token: &str, size: i64) -> (i64, i64) {
+    if size <= 0 {
+        return (0, 0);
+    }
+    use md5::Digest;
+    let md5 = hex::encode(md5::Md5::digest(access_token.as_bytes()));
+    let start = (u64::from_str_radix(&md5[..16], 16).unwrap_or_default() % size as u64) as i64;
+    (start, (start + 8).min(size))
+}
+
+/// proof_code: 证明区间数据的 base64
+pub fn adrive_proof_code(access_token: &str, data: &[u8]) -> String {
+    let (start, end) = adrive_proof_range(access_token, data.len() as i64);
+    base64::engine::general_purpose::STANDARD.encode(&data[start as usize..end as usize])
+}
+
+pub async fn adrive_source_pre_hash(source: &BoxedAdriveUploadSource) -> crate::Result<String> {
+    let size = source.size().await?.min(ADRIVE_PRE_HASH_SIZE);
+    Ok(adrive_pre_hash(&read_all(source, 0, size).await?))
+}
+
+pub async fn adrive_source_content_hash(source: &BoxedAdriveUploadSource) -> crate::Result<String> {
+    source_content_hash(source.as_ref()).await
+}
+
+pub(crate) async fn source_content_hash(source: &dyn AdriveUploadSource) -> crate::Result<String> {
+    use sha1::Digest;
+    let mut hasher = sha1::Sha1::new();
+    let mut body = source.read_range(0, source.size().await?).await?;
+    while let Some(chunk) = body.next().await {
+        hasher.update(&chunk?);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+pub async fn adrive_source_proof_code(
+    access_token: &str,
+    source: &BoxedAdriveUploadSource,
+) -> crate::Result<String> {
+    let (start, end) = adrive_proof_range(access_token, source.size().await?);
+    let data = read_all(source, start, end - start).await?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+async fn read_all(
+    source: &BoxedAdriveUploadSource,
+    offset: i64,
+    len: i64,
+) -> crate::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len as usize);
+    let mut body = source.read_range(offset, len).await?;
+    while let Some(chunk) = body.next().await {
+        data.extend_from_slice(&chunk?);
+    }
+    Ok(data)
+}
+
+fn sha1_hex(data: &[u8]) -> String {
+    use sha1::Digest;
+    hex::encode(sha1::Sha1::digest(data))
+}
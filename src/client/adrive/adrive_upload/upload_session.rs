@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+pub type BoxedAdriveUploadSessionStore = Box<dyn AdriveUploadSessionStore>;
+
+/// 进行中的上传，保存后可以在进程重启后继续上传
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct AdriveUploadSession {
+    pub drive_id: String,
+    pub file_id: String,
+    pub upload_id: String,
+    pub size: i64,
+    pub part_size: i64,
+    /// 本地文件指纹，不一致时不会续传
+    pub fingerprint: String,
+}
+
+/// 上传会话的存储，参考 OAuthClientAccessTokenStore
+#[async_trait]
+pub trait AdriveUploadSessionStore: Debug + Send + Sync {
+    async fn get_session(&self, key: &str) -> anyhow::Result<Option<AdriveUploadSession>>;
+
+    async fn set_session(&self, key: &str, session: AdriveUploadSession) -> anyhow::Result<()>;
+
+    async fn remove_session(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// 会话的 key，同一个目录下的同名文件视为同一个上传
+pub fn adrive_upload_session_key(drive_id: &str, parent_file_id: &str, name: &str) -> String {
+    format!("{}/{}/{}", drive_id, parent_file_id, name)
+}
@@ -0,0 +1,171 @@
+use crate::adrive_content_hash;
+use crate::rapid_upload::source_content_hash;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use std::fmt::{Debug, Formatter};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use tokio::sync::Mutex;
+use tokio_util::io::ReaderStream;
+
+pub type AdriveUploadBody = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+pub type BoxedAdriveUploadSource = Box<dyn AdriveUploadSource>;
+
+/// 上传的数据来源，需要支持按区间重复读取（分片上传、重试）
+#[async_trait]
+pub trait AdriveUploadSource: Debug + Send + Sync {
+    /// 数据总长度
+    async fn size(&self) -> crate::Result<i64>;
+
+    /// 读取 [offset, offset + len) 区间的数据
+    async fn read_range(&self, offset: i64, len: i64) -> crate::Result<AdriveUploadBody>;
+
+    /// 用于判断续传时数据是否发生变化，返回 None 时不保存上传会话，也不会续传
+    async fn fingerprint(&self) -> crate::Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// 本地文件，每次读取都会重新打开文件，所以可以同时读取多个区间
+#[derive(Debug, Clone)]
+pub struct FileUploadSource {
+    pub path: PathBuf,
+}
+
+impl FileUploadSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileUploadSource { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl AdriveUploadSource for FileUploadSource {
+    async fn size(&self) -> crate::Result<i64> {
+        Ok(tokio::fs::metadata(&self.path).await?.len() as i64)
+    }
+
+    async fn read_range(&self, offset: i64, len: i64) -> crate::Result<AdriveUploadBody> {
+        let mut file = tokio::fs::File::open(&self.path).await?;
+        file.seek(SeekFrom::Start(offset as u64)).await?;
+        Ok(Box::pin(ReaderStream::new(file.take(len as u64))))
+    }
+
+    /// 长度、修改时间和 inode，文件被替换或修改后不会续传
+    async fn fingerprint(&self) -> crate::Result<Option<String>> {
+        let metadata = tokio::fs::metadata(&self.path).await?;
+        let modified = chrono::DateTime::<chrono::Utc>::from(metadata.modified()?);
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Ok(Some(format!(
+            "file:{}:{}:{}",
+            metadata.len(),
+            modified.timestamp_nanos_opt().unwrap_or_default(),
+            inode
+        )))
+    }
+}
+
+/// 内存中的数据
+#[derive(Debug, Clone)]
+pub struct BytesUploadSource {
+    pub bytes: Bytes,
+}
+
+impl BytesUploadSource {
+    pub fn new(bytes: impl Into<Bytes>) -> Self {
+        BytesUploadSource {
+            bytes: bytes.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl AdriveUploadSource for BytesUploadSource {
+    async fn size(&self) -> crate::Result<i64> {
+        Ok(self.bytes.len() as i64)
+    }
+
+    async fn read_range(&self, offset: i64, len: i64) -> crate::Result<AdriveUploadBody> {
+        let start = (offset as usize).min(self.bytes.len());
+        let end = (start + len as usize).min(self.bytes.len());
+        let bytes = self.bytes.slice(start..end);
+        Ok(Box::pin(futures::stream::once(async move { Ok(bytes) })))
+    }
+
+    async fn fingerprint(&self) -> crate::Result<Option<String>> {
+        Ok(Some(format!("sha1:{}", adrive_content_hash(&self.bytes))))
+    }
+}
+
+/// 任意可以 seek 的 reader，多个区间共用同一个 reader，每次读取前重新 seek
+pub struct ReaderUploadSource<R> {
+    pub reader: Arc<Mutex<R>>,
+}
+
+impl<R> ReaderUploadSource<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderUploadSource {
+            reader: Arc::new(Mutex::new(reader)),
+        }
+    }
+}
+
+impl<R> Debug for ReaderUploadSource<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReaderUploadSource").finish()
+    }
+}
+
+#[async_trait]
+impl<R> AdriveUploadSource for ReaderUploadSource<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin + Send + 'static,
+{
+    async fn size(&self) -> crate::Result<i64> {
+        let mut reader = self.reader.lock().await;
+        Ok(reader.seek(SeekFrom::End(0)).await? as i64)
+    }
+
+    /// 每读取一块数据时独占 reader 并 seek 到对应的位置，发送数据时不占用，多个分片可以同时上传
+    async fn read_range(&self, offset: i64, len: i64) -> crate::Result<AdriveUploadBody> {
+        let stream = futures::stream::unfold(
+            (self.reader.clone(), offset as u64, len as u64),
+            |(reader, position, remaining)| async move {
+                if remaining == 0 {
+                    return None;
+                }
+                let mut buffer = vec![0u8; remaining.min(1 << 16) as usize];
+                let read = async {
+                    let mut reader = reader.lock().await;
+                    reader.seek(SeekFrom::Start(position)).await?;
+                    reader.read(&mut buffer).await
+                }
+                .await;
+                match read {
+                    Ok(0) => None,
+                    Ok(n) => {
+                        buffer.truncate(n);
+                        Some((
+                            Ok(Bytes::from(buffer)),
+                            (reader, position + n as u64, remaining - n as u64),
+                        ))
+                    }
+                    Err(err) => Some((Err(err), (reader, position, 0))),
+                }
+            },
+        );
+        Ok(Box::pin(stream))
+    }
+
+    /// 内容的 sha1，需要完整读取一遍数据
+    async fn fingerprint(&self) -> crate::Result<Option<String>> {
+        Ok(Some(format!("sha1:{}", source_content_hash(self).await?)))
+    }
+}
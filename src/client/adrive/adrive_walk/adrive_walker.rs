@@ -0,0 +1,211 @@
+use crate::{AdriveClient, AdriveOpenFile, AdriveOpenFileType, OptionParam};
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::{Stream, TryStreamExt};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// 返回 false 时跳过这个条目，如果是文件夹也不会继续遍历
+pub type AdriveWalkFilter = Arc<dyn Fn(&AdriveWalkEntry) -> bool + Send + Sync>;
+
+impl AdriveClient {
+    pub async fn adrive_walker(&self) -> AdriveWalker<'_> {
+        AdriveWalker {
+            client: self,
+            drive_id: None.into(),
+            parent_file_id: "root".to_string(),
+            order: AdriveWalkOrder::BreadthFirst,
+            max_depth: None.into(),
+            concurrency: 4,
+            limit: None.into(),
+            filter: None.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdriveWalkOrder {
+    /// 广度优先，一层遍历完成后再遍历下一层
+    BreadthFirst,
+    /// 深度优先，先遍历最近发现的文件夹
+    DepthFirst,
+}
+
+/// 遍历到的文件或文件夹
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdriveWalkEntry {
+    /// 相对于遍历起点的路径，用 `/` 分隔
+    pub path: String,
+    /// 起点下的直接子项为 1
+    pub depth: usize,
+    pub file: AdriveOpenFile,
+}
+
+/// 递归遍历文件夹
+pub struct AdriveWalker<'a> {
+    pub client: &'a AdriveClient,
+    pub drive_id: OptionParam<String>,
+    /// 遍历的起点，不包含在结果中
+    pub parent_file_id: String,
+    pub order: AdriveWalkOrder,
+    /// 最大深度，为 1 时只列出起点下的直接子项
+    pub max_depth: OptionParam<usize>,
+    /// 同时列出的文件夹数
+    pub concurrency: usize,
+    /// 每页的数量
+    pub limit: OptionParam<i64>,
+    pub filter: OptionParam<AdriveWalkFilter>,
+}
+
+impl<'a> Debug for AdriveWalker<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdriveWalker")
+            .field("drive_id", &self.drive_id)
+            .field("parent_file_id", &self.parent_file_id)
+            .field("order", &self.order)
+            .field("max_depth", &self.max_depth)
+            .field("concurrency", &self.concurrency)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<'a> AdriveWalker<'a> {
+    pub fn drive_id(mut self, drive_id: impl Into<OptionParam<String>>) -> Self {
+        self.drive_id = drive_id.into();
+        self
+    }
+
+    pub fn parent_file_id(mut self, parent_file_id: impl Into<String>) -> Self {
+        self.parent_file_id = parent_file_id.into();
+        self
+    }
+
+    pub fn order(mut self, order: AdriveWalkOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: impl Into<OptionParam<usize>>) -> Self {
+        self.max_depth = max_depth.into();
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn limit(mut self, limit: impl Into<OptionParam<i64>>) -> Self {
+        self.limit = limit.into();
+        self
+    }
+
+    pub fn filter(
+        mut self,
+        filter: impl Fn(&AdriveWalkEntry) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = (Arc::new(filter) as AdriveWalkFilter).into();
+        self
+    }
+}
+
+/// 等待列出的文件夹
+struct WalkFolder {
+    file_id: String,
+    path: String,
+    depth: usize,
+}
+
+type WalkListing<'a> = BoxFuture<'a, crate::Result<(WalkFolder, Vec<AdriveOpenFile>)>>;
+
+impl<'a> AdriveWalker<'a> {
+    /// 依次返回遍历到的条目，同时最多列出 concurrency 个文件夹，先列完的文件夹先返回
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<AdriveWalkEntry>> + Send + 'a {
+        let root = WalkFolder {
+            file_id: self.parent_file_id.clone(),
+            path: "".to_string(),
+            depth: 0,
+        };
+        futures::stream::try_unfold(
+            (self, VecDeque::from([root]), FuturesUnordered::new()),
+            |(walker, mut queue, mut listing): (_, _, FuturesUnordered<WalkListing<'a>>)| async move {
+                let concurrency = walker.concurrency.max(1);
+                while listing.len() < concurrency {
+                    let folder = match walker.order {
+                        AdriveWalkOrder::BreadthFirst => queue.pop_front(),
+                        AdriveWalkOrder::DepthFirst => queue.pop_back(),
+                    };
+                    match folder {
+                        Some(folder) => listing.push(walker.list(folder)),
+                        None => break,
+                    }
+                }
+                let (folder, files) = match listing.try_next().await? {
+                    Some(listed) => listed,
+                    None => return crate::Result::Ok(None),
+                };
+                let mut entries = vec![];
+                let mut children = vec![];
+                for file in files {
+                    let entry = AdriveWalkEntry {
+                        path: if folder.path.is_empty() {
+                            file.name.clone()
+                        } else {
+                            format!("{}/{}", folder.path, file.name)
+                        },
+                        depth: folder.depth + 1,
+                        file,
+                    };
+                    if let Some(filter) = walker.filter.deref() {
+                        if !filter(&entry) {
+                            continue;
+                        }
+                    }
+                    if entry.file.r#type == AdriveOpenFileType::Folder
+                        && walker.max_depth.is_none_or(|max| entry.depth < max)
+                    {
+                        children.push(WalkFolder {
+                            file_id: entry.file.file_id.clone(),
+                            path: entry.path.clone(),
+                            depth: entry.depth,
+                        });
+                    }
+                    entries.push(Ok(entry));
+                }
+                match walker.order {
+                    AdriveWalkOrder::BreadthFirst => queue.extend(children),
+                    // 栈顶是第一个子文件夹
+                    AdriveWalkOrder::DepthFirst => queue.extend(children.into_iter().rev()),
+                }
+                Ok(Some((futures::stream::iter(entries), (walker, queue, listing))))
+            },
+        )
+        .try_flatten()
+    }
+
+    fn list(&self, folder: WalkFolder) -> WalkListing<'a> {
+        let client = self.client;
+        let drive_id = self.drive_id.deref().clone();
+        let limit = *self.limit.deref();
+        Box::pin(async move {
+            let drive_id = if let Some(drive_id) = drive_id {
+                drive_id
+            } else {
+                return Err(crate::Error::require_param_missing("drive_id"));
+            };
+            let files = client
+                .adrive_open_file_list()
+                .await
+                .drive_id(drive_id)
+                .parent_file_id(folder.file_id.as_str())
+                .limit(limit)
+                .into_stream()
+                .try_collect()
+                .await?;
+            Ok((folder, files))
+        })
+    }
+}
@@ -0,0 +1,3 @@
+pub mod adrive_walker;
+
+pub use adrive_walker::*;
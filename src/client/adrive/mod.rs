@@ -1,5 +1,15 @@
 pub mod adrive_api;
 pub mod adrive_client;
+pub mod adrive_download;
+pub mod adrive_path;
+pub mod adrive_task;
+pub mod adrive_upload;
+pub mod adrive_walk;
 
 pub use adrive_api::*;
 pub use adrive_client::*;
+pub use adrive_download::*;
+pub use adrive_path::*;
+pub use adrive_task::*;
+pub use adrive_upload::*;
+pub use adrive_walk::*;
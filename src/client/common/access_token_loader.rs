@@ -1,9 +1,11 @@
 use crate::oauth_access_token::OauthAccessToken;
 use crate::{GrantType, OAuthClient};
 use async_trait::async_trait;
+use futures::future::{BoxFuture, Shared};
+use futures::{FutureExt, TryFutureExt};
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 pub type BoxedAccessTokenLoader = Box<dyn AccessTokenLoader>;
 
@@ -43,10 +45,31 @@ impl AccessTokenLoader for UninitializedAccessTokenLoader {
     }
 }
 
+/// 使用 RefreshToken 自动续期的 AccessToken
+///
+/// 令牌缓存在内存中，同一时间只会有一个续期请求，其它调用等待并使用它的结果
 #[derive(Debug)]
 pub struct OAuthClientAccessTokenManager {
     pub oauth_client: Arc<OAuthClient>,
     pub access_token_store: Arc<Box<dyn OAuthClientAccessTokenStore>>,
+    state: Mutex<AccessTokenState>,
+}
+
+type SharedRefresh = Shared<BoxFuture<'static, Result<AccessToken, Arc<anyhow::Error>>>>;
+
+#[derive(Default)]
+struct AccessTokenState {
+    cached: Option<AccessToken>,
+    refreshing: Option<SharedRefresh>,
+}
+
+impl Debug for AccessTokenState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessTokenState")
+            .field("cached", &self.cached)
+            .field("refreshing", &self.refreshing.is_some())
+            .finish()
+    }
 }
 
 #[async_trait]
@@ -56,32 +79,87 @@ pub trait OAuthClientAccessTokenStore: Debug + Send + Sync {
     async fn set_access_token(&self, access_token: AccessToken) -> anyhow::Result<()>;
 }
 
+impl OAuthClientAccessTokenManager {
+    pub fn new(
+        oauth_client: impl Into<Arc<OAuthClient>>,
+        access_token_store: impl Into<Arc<Box<dyn OAuthClientAccessTokenStore>>>,
+    ) -> Self {
+        Self {
+            oauth_client: oauth_client.into(),
+            access_token_store: access_token_store.into(),
+            state: Mutex::new(AccessTokenState::default()),
+        }
+    }
+
+    /// 清除内存中的令牌，下次使用时重新从存储中读取
+    pub fn clear_cache(&self) {
+        self.state.lock().unwrap().cached = None;
+    }
+
+    fn refresh(&self) -> SharedRefresh {
+        let oauth_client = self.oauth_client.clone();
+        let access_token_store = self.access_token_store.clone();
+        async move {
+            // 其它实例可能已经续期并写入了存储
+            let token = match access_token_store.get_access_token().await? {
+                Some(token) if is_fresh(&token) => return Ok(token),
+                Some(token) => token,
+                None => return Err(anyhow::Error::msg("no access token")),
+            };
+            let token = oauth_client
+                .oauth_access_token()
+                .await
+                .grant_type(GrantType::RefreshToken)
+                .refresh_token(token.refresh_token.as_str())
+                .request()
+                .await?;
+            let access_token = AccessToken::wrap_oauth_token(token);
+            access_token_store
+                .set_access_token(access_token.clone())
+                .await?;
+            Ok(access_token)
+        }
+        .map_err(Arc::new)
+        .boxed()
+        .shared()
+    }
+}
+
+/// 已经过了 3/4 有效期的令牌需要续期
+fn is_fresh(token: &AccessToken) -> bool {
+    chrono::Utc::now().timestamp() - token.created_at < token.expires_in * 3 / 4
+}
+
 #[async_trait]
 impl AccessTokenLoader for OAuthClientAccessTokenManager {
     async fn get_access_token(&self) -> anyhow::Result<AccessToken> {
-        let token = self.access_token_store.get_access_token().await?;
-        let token = match token {
-            Some(token) => {
-                let now = chrono::Utc::now().timestamp();
-                if now - token.created_at < token.expires_in * 3 / 4 {
-                    return Ok(token);
-                }
-                token
+        let refreshing = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(token) = state.cached.as_ref().filter(|token| is_fresh(token)) {
+                return Ok(token.clone());
             }
-            None => return Err(anyhow::Error::msg("no access token")),
+            state
+                .refreshing
+                .get_or_insert_with(|| self.refresh())
+                .clone()
         };
-        let token = self
-            .oauth_client
-            .oauth_access_token()
-            .await
-            .grant_type(GrantType::RefreshToken)
-            .refresh_token(token.refresh_token.as_str())
-            .request()
-            .await?;
-        let access_token = AccessToken::wrap_oauth_token(token);
-        self.access_token_store
-            .set_access_token(access_token.clone())
-            .await?;
-        Ok(access_token)
+        let result = refreshing.clone().await;
+        {
+            let mut state = self.state.lock().unwrap();
+            if state
+                .refreshing
+                .as_ref()
+                .is_some_and(|current| current.ptr_eq(&refreshing))
+            {
+                state.refreshing = None;
+                if let Ok(token) = &result {
+                    state.cached = Some(token.clone());
+                }
+            }
+        }
+        drop(refreshing);
+        result.map_err(|err| {
+            Arc::try_unwrap(err).unwrap_or_else(|err| anyhow::Error::msg(format!("{:#}", err)))
+        })
     }
 }
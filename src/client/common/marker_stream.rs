@@ -0,0 +1,38 @@
+use futures::{Future, Stream, TryStreamExt};
+
+/// 通过 marker 翻页的请求
+pub(crate) trait MarkerRequest: Sized + Send + Sync {
+    type Page: Send;
+
+    fn request_page(&self) -> impl Future<Output = crate::Result<Self::Page>> + Send;
+
+    /// 设置下一页的 marker
+    fn with_marker(self, marker: String) -> Self;
+}
+
+/// 从请求当前的 marker 开始自动翻页，split 取出每页的数据和下一页的 marker，没有 marker 时结束
+pub(crate) fn marker_stream<R, T, F>(
+    request: R,
+    split: F,
+) -> impl Stream<Item = crate::Result<T>> + Send
+where
+    R: MarkerRequest,
+    T: Send,
+    F: Fn(R::Page) -> (Vec<T>, Option<String>) + Copy + Send,
+{
+    futures::stream::try_unfold(Some(request), move |request| async move {
+        let request = if let Some(request) = request {
+            request
+        } else {
+            return crate::Result::Ok(None);
+        };
+        let page = request.request_page().await?;
+        let (items, next_marker) = split(page);
+        let next = next_marker.map(|marker| request.with_marker(marker));
+        Ok(Some((
+            futures::stream::iter(items.into_iter().map(Ok)),
+            next,
+        )))
+    })
+    .try_flatten()
+}
@@ -1,7 +1,12 @@
 pub mod access_token_loader;
 pub mod load_access_token;
+pub(crate) mod marker_stream;
+pub mod rate_limiter;
 pub mod response;
+pub mod transfer_progress;
 
 pub use access_token_loader::*;
 pub use load_access_token::*;
+pub use rate_limiter::*;
 pub use response::*;
+pub use transfer_progress::*;
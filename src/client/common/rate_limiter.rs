@@ -0,0 +1,74 @@
+use bytes::Bytes;
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 限速时每次最多放行的字节数，避免一个大分片一次性发出
+const RATE_LIMIT_CHUNK_SIZE: usize = 16 << 10;
+
+/// 传输限速（字节/秒），clone 出来的限速器共享同一个额度
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bytes_per_second: u64,
+    next: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_second: u64) -> Self {
+        RateLimiter {
+            bytes_per_second: bytes_per_second.max(1),
+            next: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// 等待到可以传输 bytes 个字节
+    pub async fn acquire(&self, bytes: u64) {
+        let start = {
+            let mut next = self.next.lock().unwrap();
+            let start = (*next).max(Instant::now());
+            *next = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
+
+/// 按限速器放行数据流，没有限速器时原样返回
+pub(crate) fn rate_limit_stream<S>(
+    stream: S,
+    rate_limiter: Option<RateLimiter>,
+) -> Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>
+where
+    S: Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+{
+    let rate_limiter = match rate_limiter {
+        Some(rate_limiter) => rate_limiter,
+        None => return Box::pin(stream),
+    };
+    let stream = stream
+        .map_ok(|mut chunk| {
+            let mut chunks = vec![];
+            while chunk.len() > RATE_LIMIT_CHUNK_SIZE {
+                chunks.push(Ok(chunk.split_to(RATE_LIMIT_CHUNK_SIZE)));
+            }
+            chunks.push(Ok(chunk));
+            futures::stream::iter(chunks)
+        })
+        .try_flatten()
+        .then(move |chunk| {
+            let rate_limiter = rate_limiter.clone();
+            async move {
+                if let Ok(chunk) = &chunk {
+                    rate_limiter.acquire(chunk.len() as u64).await;
+                }
+                chunk
+            }
+        });
+    Box::pin(stream)
+}
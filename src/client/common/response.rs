@@ -1,7 +1,7 @@
-use crate::AdriveOpenFileType;
+use crate::{AdriveOpenFileType, AdriveVideoTranscodingTask};
 use chrono::Utc;
 use serde_derive::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct AdriveOpenFileList {
@@ -31,14 +31,91 @@ pub struct AdriveOpenFile {
     pub updated_at: chrono::DateTime<Utc>,
     /* only list */
     pub play_cursor: Option<String>,
-    pub video_media_metadata: Option<Value>,
-    pub video_preview_metadata: Option<Value>,
+    pub video_media_metadata: Option<AdriveVideoMediaMetadata>,
+    pub video_preview_metadata: Option<AdriveVideoPreviewMetadata>,
     /* only get */
     pub items: Option<Vec<Value>>,
     pub id_path: Option<String>,
     pub name_path: Option<String>,
 }
 
+/// 视频文件的媒体信息，未列出的字段保存在 extra 中
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoMediaMetadata {
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub width: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub height: Option<i64>,
+    /// 秒，可能带小数
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub duration: Option<String>,
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub video_media_video_stream: Vec<AdriveVideoMediaVideoStream>,
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub video_media_audio_stream: Vec<AdriveVideoMediaAudioStream>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoMediaVideoStream {
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub code_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub bitrate: Option<String>,
+    /// 例如 `30/1`
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub fps: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub clarity: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub duration: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoMediaAudioStream {
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub code_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub bit_rate: Option<String>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub channels: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub channel_layout: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub sample_rate: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub duration: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 视频转码后的信息，未列出的字段保存在 extra 中
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct AdriveVideoPreviewMetadata {
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub width: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_i64")]
+    pub height: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub duration: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub bitrate: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub frame_rate: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub video_format: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub audio_format: Option<String>,
+    /// 各个清晰度的转码状态
+    #[serde(default, deserialize_with = "null_to_default")]
+    pub template_list: Vec<AdriveVideoTranscodingTask>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
 pub fn null_to_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -49,13 +126,53 @@ where
     Ok(opt.unwrap_or_default())
 }
 
-fn blank_to_null<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+pub(crate) fn blank_to_null<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     use serde::Deserialize;
     let opt = Option::deserialize(deserializer)?;
-    Ok(opt
-        .map(|s: String| if s.is_empty() { None } else { Some(s) })
-        .flatten())
+    Ok(opt.and_then(|s: String| if s.is_empty() { None } else { Some(s) }))
+}
+
+/// 字符串或数字都转换为字符串，其他类型视为 None
+pub(crate) fn lenient_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// RFC 3339 格式的时间，无法解析时视为 None
+pub(crate) fn lenient_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<chrono::DateTime<Utc>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s.as_str())
+            .ok()
+            .map(|time| time.with_timezone(&Utc)),
+        _ => None,
+    })
+}
+
+/// 整数或者数字字符串，无法解析时视为 None
+pub(crate) fn lenient_i64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_i64().or(n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
 }
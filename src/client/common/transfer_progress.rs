@@ -0,0 +1,76 @@
+use crate::AlipanError;
+use bytes::Bytes;
+use futures::{Stream, TryStreamExt};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+pub use tokio_util::sync::CancellationToken;
+
+pub type BoxedTransferProgress = Box<dyn TransferProgress>;
+
+/// 上传、下载的进度回调，在传输过程中同步调用，实现时不要阻塞
+///
+/// 所有方法都有空的默认实现，只需要实现关心的事件
+pub trait TransferProgress: Debug + Send + Sync {
+    /// 开始传输，total 为需要传输的总字节数，未知长度时为 None；续传时不包含已完成的部分
+    fn on_start(&self, _total: Option<i64>) {}
+
+    /// 又传输了 bytes 个字节
+    fn on_bytes(&self, _bytes: u64) {}
+
+    /// 分片传输完成
+    fn on_part_completed(&self, _part_number: i64, _len: i64) {}
+
+    /// 分片传输失败，即将重试；discarded 为这次失败前已经通过 on_bytes 报告的字节数
+    fn on_retry(&self, _part_number: i64, _retry: usize, _discarded: u64, _error: &AlipanError) {}
+}
+
+/// 调用方可以保留 Arc 读取自己记录的进度
+impl<T: TransferProgress + ?Sized> TransferProgress for Arc<T> {
+    fn on_start(&self, total: Option<i64>) {
+        self.as_ref().on_start(total)
+    }
+
+    fn on_bytes(&self, bytes: u64) {
+        self.as_ref().on_bytes(bytes)
+    }
+
+    fn on_part_completed(&self, part_number: i64, len: i64) {
+        self.as_ref().on_part_completed(part_number, len)
+    }
+
+    fn on_retry(&self, part_number: i64, retry: usize, discarded: u64, error: &AlipanError) {
+        self.as_ref().on_retry(part_number, retry, discarded, error)
+    }
+}
+
+/// 在数据流经过时报告进度，同时累加到 counter，失败重试时用来回退进度
+pub(crate) fn progress_stream<S>(
+    stream: S,
+    progress: Option<Arc<BoxedTransferProgress>>,
+    counter: Arc<AtomicU64>,
+) -> impl Stream<Item = std::io::Result<Bytes>> + Send
+where
+    S: Stream<Item = std::io::Result<Bytes>> + Send,
+{
+    stream.inspect_ok(move |chunk| {
+        counter.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        if let Some(progress) = &progress {
+            progress.on_bytes(chunk.len() as u64);
+        }
+    })
+}
+
+/// 取消时返回 ErrorInfo::Cancelled，正在进行的请求会被丢弃
+pub(crate) async fn cancellable<T>(
+    cancellation_token: Option<&CancellationToken>,
+    future: impl std::future::Future<Output = crate::Result<T>>,
+) -> crate::Result<T> {
+    match cancellation_token {
+        Some(cancellation_token) => cancellation_token
+            .run_until_cancelled(future)
+            .await
+            .unwrap_or_else(|| Err(AlipanError::new(crate::ErrorInfo::Cancelled))),
+        None => future.await,
+    }
+}
@@ -1,5 +1,13 @@
 pub mod oauth_api;
 pub mod oauth_client;
+pub mod oauth_loopback_login;
+pub mod oauth_pkce;
+pub mod oauth_qrcode_login;
+pub mod oauth_state;
 
 pub use oauth_api::*;
 pub use oauth_client::*;
+pub use oauth_loopback_login::*;
+pub use oauth_pkce::*;
+pub use oauth_qrcode_login::*;
+pub use oauth_state::*;
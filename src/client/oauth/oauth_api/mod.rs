@@ -1,4 +1,8 @@
 pub mod oauth_access_token;
 pub mod oauth_authorize;
+pub mod oauth_authorize_qrcode;
+pub mod oauth_qrcode_status;
 
 pub use oauth_authorize::*;
+pub use oauth_authorize_qrcode::*;
+pub use oauth_qrcode_status::*;
@@ -27,7 +27,7 @@ pub struct OauthAccessTokenRequest {
     pub api_host: Arc<String>,
     /// 创建应用时分配的 appId
     pub client_id: Arc<String>,
-    /// 创建应用时分配的 appSecret
+    /// 创建应用时分配的 appSecret，使用 PKCE 时可以为空
     pub client_secret: Arc<String>,
     /// 仅支持 authorization_code
     pub grant_type: OptionParam<GrantType>,
@@ -81,7 +81,10 @@ impl OauthAccessTokenRequest {
     pub async fn request(&self) -> crate::Result<OauthAccessToken> {
         let mut form = HashMap::<&str, &str>::new();
         form.insert("client_id", self.client_id.as_str());
-        form.insert("client_secret", self.client_secret.as_str());
+        // 使用 PKCE 的公开客户端没有 client_secret
+        if !self.client_secret.is_empty() {
+            form.insert("client_secret", self.client_secret.as_str());
+        }
         if let Some(grant_type) = self.grant_type.deref() {
             match grant_type {
                 GrantType::AuthorizationCode => {
@@ -92,6 +95,10 @@ impl OauthAccessTokenRequest {
                     }
                     if let Some(code_verifier) = &self.code_verifier.deref() {
                         form.insert("code_verifier", code_verifier.as_str());
+                    } else if self.client_secret.is_empty() {
+                        return Err(AlipanError::require_param_missing(
+                            "client_secret or code_verifier",
+                        ));
                     }
                 }
                 GrantType::RefreshToken => {
@@ -1,4 +1,4 @@
-use crate::{Error, OAuthClient};
+use crate::{Error, OAuthClient, OauthPkce};
 use std::sync::Arc;
 
 impl OAuthClient {
@@ -12,6 +12,8 @@ impl OAuthClient {
             state: None,
             relogin: None,
             drive: None,
+            code_challenge: None,
+            code_challenge_method: None,
         }
     }
 }
@@ -33,6 +35,10 @@ pub struct OauthAuthorizeUrl {
     pub relogin: Option<bool>,
     /// 指定必选的drive,  backup 或 resource。多个用 , 分隔。例如：backup,resource
     pub drive: Option<String>,
+    /// PKCE，由 code_verifier 计算得到
+    pub code_challenge: Option<String>,
+    /// S256 或 plain
+    pub code_challenge_method: Option<String>,
 }
 
 impl OauthAuthorizeUrl {
@@ -76,6 +82,22 @@ impl OauthAuthorizeUrl {
         self
     }
 
+    pub fn code_challenge(mut self, code_challenge: impl Into<String>) -> Self {
+        self.code_challenge = Some(code_challenge.into());
+        self
+    }
+
+    pub fn code_challenge_method(mut self, code_challenge_method: impl Into<String>) -> Self {
+        self.code_challenge_method = Some(code_challenge_method.into());
+        self
+    }
+
+    /// 设置 code_challenge 和 code_challenge_method，换取 AccessToken 时需要提交同一个 code_verifier
+    pub fn pkce(self, pkce: &OauthPkce) -> Self {
+        self.code_challenge(pkce.code_challenge.as_str())
+            .code_challenge_method(pkce.code_challenge_method.as_str())
+    }
+
     pub fn build(&self) -> crate::Result<String> {
         if self.client_id.is_empty() {
             return Err(Error::require_param_missing("client_id"));
@@ -103,6 +125,14 @@ impl OauthAuthorizeUrl {
         if let Some(drive) = &self.drive {
             url.query_pairs_mut().append_pair("drive", drive.as_str());
         }
+        if let Some(code_challenge) = &self.code_challenge {
+            url.query_pairs_mut()
+                .append_pair("code_challenge", code_challenge.as_str());
+            url.query_pairs_mut().append_pair(
+                "code_challenge_method",
+                self.code_challenge_method.as_deref().unwrap_or("S256"),
+            );
+        }
         Ok(url.to_string())
     }
 }
@@ -0,0 +1,143 @@
+use crate::{response, OAuthClient, OauthPkce, OptionParam};
+use serde_derive::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl OAuthClient {
+    /// 获取扫码登录的二维码
+    pub async fn oauth_authorize_qrcode(&self) -> OauthAuthorizeQrCodeRequest {
+        OauthAuthorizeQrCodeRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            client_id: self.clone_client_id().await,
+            client_secret: self.clone_client_secret().await,
+            scopes: vec![],
+            width: None.into(),
+            height: None.into(),
+            code_challenge: None.into(),
+            code_challenge_method: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OauthAuthorizeQrCodeRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub client_id: Arc<String>,
+    /// 使用 PKCE 时可以为空
+    pub client_secret: Arc<String>,
+    /// 申请的授权范围，例如 user:base、file:all:read
+    pub scopes: Vec<String>,
+    /// 二维码图片的宽度
+    pub width: OptionParam<i64>,
+    pub height: OptionParam<i64>,
+    pub code_challenge: OptionParam<String>,
+    pub code_challenge_method: OptionParam<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct OauthAuthorizeQrCodeRequestPost {
+    pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub scopes: Vec<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_challenge: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_challenge_method: Option<String>,
+}
+
+impl OauthAuthorizeQrCodeRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Arc::new(client_id.into());
+        self
+    }
+
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Arc::new(client_secret.into());
+        self
+    }
+
+    pub fn scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<OptionParam<i64>>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn height(mut self, height: impl Into<OptionParam<i64>>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    pub fn code_challenge(mut self, code_challenge: impl Into<OptionParam<String>>) -> Self {
+        self.code_challenge = code_challenge.into();
+        self
+    }
+
+    pub fn code_challenge_method(
+        mut self,
+        code_challenge_method: impl Into<OptionParam<String>>,
+    ) -> Self {
+        self.code_challenge_method = code_challenge_method.into();
+        self
+    }
+
+    pub fn pkce(self, pkce: &OauthPkce) -> Self {
+        self.code_challenge(pkce.code_challenge.as_str())
+            .code_challenge_method(pkce.code_challenge_method.as_str())
+    }
+
+    pub async fn request(&self) -> crate::Result<OauthAuthorizeQrCode> {
+        if self.client_id.is_empty() {
+            return Err(crate::Error::require_param_missing("client_id"));
+        }
+        if self.scopes.is_empty() {
+            return Err(crate::Error::require_param_missing("scopes"));
+        }
+        let resp = self
+            .agent
+            .post(format!("{}/oauth/authorize/qrcode", self.api_host.as_str()).as_str())
+            .json(&OauthAuthorizeQrCodeRequestPost {
+                client_id: self.client_id.to_string(),
+                client_secret: if self.client_secret.is_empty() {
+                    None
+                } else {
+                    Some(self.client_secret.to_string())
+                },
+                scopes: self.scopes.clone(),
+                width: *self.width.deref(),
+                height: *self.height.deref(),
+                code_challenge: self.code_challenge.deref().clone(),
+                code_challenge_method: self.code_challenge_method.deref().clone(),
+            })
+            .send()
+            .await?;
+        response(resp).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct OauthAuthorizeQrCode {
+    /// 二维码图片的地址
+    #[serde(rename = "qrCodeUrl")]
+    pub qr_code_url: String,
+    /// 查询扫码状态时使用
+    pub sid: String,
+}
@@ -0,0 +1,61 @@
+use crate::{response, OAuthClient, OauthQrCodeStatus, OptionParam};
+use serde_derive::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl OAuthClient {
+    /// 查询二维码的扫码状态
+    pub async fn oauth_qrcode_status(&self) -> OauthQrCodeStatusRequest {
+        OauthQrCodeStatusRequest {
+            agent: self.clone_agent().await,
+            api_host: self.clone_api_host().await,
+            sid: None.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OauthQrCodeStatusRequest {
+    pub agent: Arc<reqwest::Client>,
+    pub api_host: Arc<String>,
+    pub sid: OptionParam<String>,
+}
+
+impl OauthQrCodeStatusRequest {
+    pub fn agent(mut self, agent: impl Into<Arc<reqwest::Client>>) -> Self {
+        self.agent = agent.into();
+        self
+    }
+
+    pub fn api_host(mut self, api_host: impl Into<Arc<String>>) -> Self {
+        self.api_host = api_host.into();
+        self
+    }
+
+    pub fn sid(mut self, sid: impl Into<OptionParam<String>>) -> Self {
+        self.sid = sid.into();
+        self
+    }
+
+    pub async fn request(&self) -> crate::Result<OauthQrCodeStatusResult> {
+        let sid = if let Some(sid) = self.sid.deref() {
+            sid
+        } else {
+            return Err(crate::Error::require_param_missing("sid"));
+        };
+        let resp = self
+            .agent
+            .get(format!("{}/oauth/qrcode/{}/status", self.api_host.as_str(), sid).as_str())
+            .send()
+            .await?;
+        response(resp).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct OauthQrCodeStatusResult {
+    pub status: OauthQrCodeStatus,
+    /// 状态为 LoginSuccess 时返回，用于换取 AccessToken
+    #[serde(default, rename = "authCode")]
+    pub auth_code: Option<String>,
+}
@@ -0,0 +1,291 @@
+use crate::oauth_access_token::OauthAccessToken;
+use crate::oauth_pkce::random_url_safe;
+use crate::{AlipanError, ErrorInfo, GrantType, OAuthClient, OauthCallback, OauthPkce};
+use futures::future::Either;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Instant;
+
+/// 连接建立后读取请求的超时时间
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 请求行和每个请求头的最大长度
+const MAX_REQUEST_LINE_LEN: usize = 8 << 10;
+
+const MAX_REQUEST_HEADERS: usize = 100;
+
+/// 授权成功后浏览器中显示的页面
+pub const OAUTH_LOOPBACK_SUCCESS_HTML: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>授权成功</title></head><body><p>授权成功，可以关闭此页面。</p><p>You can close this tab now.</p></body></html>";
+
+/// 授权失败时浏览器中显示的页面
+pub const OAUTH_LOOPBACK_FAILURE_HTML: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>授权失败</title></head><body><p>授权失败，请回到应用中重试。</p><p>Authorization failed, please return to the application and try again.</p></body></html>";
+
+impl OAuthClient {
+    /// 在 127.0.0.1 上监听授权回调，适用于桌面应用
+    ///
+    /// redirect_uri 为 `http://127.0.0.1:{port}{callback_path}`，需要在开放平台中登记
+    pub async fn oauth_loopback_login(&self) -> OauthLoopbackLogin<'_> {
+        OauthLoopbackLogin {
+            client: self,
+            scope: "".to_string(),
+            port: 0,
+            callback_path: "/callback".to_string(),
+            relogin: None,
+            drive: None,
+            pkce: None,
+            timeout: Duration::from_secs(300),
+            success_html: OAUTH_LOOPBACK_SUCCESS_HTML.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OauthLoopbackLogin<'a> {
+    pub client: &'a OAuthClient,
+    /// 多个用逗号分隔，例如 user:base,file:all:read
+    pub scope: String,
+    /// 为 0 时由系统分配端口
+    pub port: u16,
+    pub callback_path: String,
+    pub relogin: Option<bool>,
+    pub drive: Option<String>,
+    /// 设置后使用 PKCE，不需要 client_secret
+    pub pkce: Option<OauthPkce>,
+    /// 超过这个时间没有收到回调时返回 OauthLoginTimeout
+    pub timeout: Duration,
+    pub success_html: String,
+}
+
+impl<'a> OauthLoopbackLogin<'a> {
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = scope.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn callback_path(mut self, callback_path: impl Into<String>) -> Self {
+        self.callback_path = callback_path.into();
+        self
+    }
+
+    pub fn relogin(mut self, relogin: impl Into<Option<bool>>) -> Self {
+        self.relogin = relogin.into();
+        self
+    }
+
+    pub fn drive(mut self, drive: impl Into<Option<String>>) -> Self {
+        self.drive = drive.into();
+        self
+    }
+
+    pub fn pkce(mut self, pkce: impl Into<Option<OauthPkce>>) -> Self {
+        self.pkce = pkce.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn success_html(mut self, success_html: impl Into<String>) -> Self {
+        self.success_html = success_html.into();
+        self
+    }
+
+    /// 开始监听并生成授权链接，在浏览器中打开 authorize_url 之后调用 OauthLoopbackLoginSession::wait
+    pub async fn start(self) -> crate::Result<OauthLoopbackLoginSession<'a>> {
+        if !self.callback_path.starts_with('/') {
+            return Err(AlipanError::msg("callback_path must start with '/'"));
+        }
+        let listener = TcpListener::bind(("127.0.0.1", self.port)).await?;
+        let redirect_uri = format!(
+            "http://127.0.0.1:{}{}",
+            listener.local_addr()?.port(),
+            self.callback_path
+        );
+        let state = random_url_safe(16)?;
+        let mut authorize = self
+            .client
+            .oauth_authorize()
+            .await
+            .redirect_uri(redirect_uri.as_str())
+            .scope(self.scope.as_str())
+            .state(state.as_str());
+        if let Some(relogin) = self.relogin {
+            authorize = authorize.relogin(relogin);
+        }
+        if let Some(drive) = &self.drive {
+            authorize = authorize.drive(drive.as_str());
+        }
+        if let Some(pkce) = &self.pkce {
+            authorize = authorize.pkce(pkce);
+        }
+        let authorize_url = authorize.build()?;
+        Ok(OauthLoopbackLoginSession {
+            listener,
+            authorize_url,
+            redirect_uri,
+            state,
+            deadline: Instant::now() + self.timeout,
+            login: self,
+        })
+    }
+}
+
+/// 正在监听的授权回调
+#[derive(Debug)]
+pub struct OauthLoopbackLoginSession<'a> {
+    pub login: OauthLoopbackLogin<'a>,
+    pub authorize_url: String,
+    pub redirect_uri: String,
+    pub state: String,
+    listener: TcpListener,
+    deadline: Instant,
+}
+
+impl<'a> OauthLoopbackLoginSession<'a> {
+    /// 需要在浏览器中打开的授权链接
+    pub fn authorize_url(&self) -> &str {
+        self.authorize_url.as_str()
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        self.redirect_uri.as_str()
+    }
+
+    /// 等待浏览器跳转到 redirect_uri，校验 state 后使用 code 换取 AccessToken
+    pub async fn wait(self) -> crate::Result<OauthAccessToken> {
+        let code = match tokio::time::timeout_at(self.deadline, self.accept_code()).await {
+            Ok(code) => code?,
+            Err(_) => return Err(AlipanError::new(ErrorInfo::OauthLoginTimeout)),
+        };
+        self.login
+            .client
+            .oauth_access_token()
+            .await
+            .grant_type(GrantType::AuthorizationCode)
+            .code(code)
+            .code_verifier(
+                self.login
+                    .pkce
+                    .as_ref()
+                    .map(|pkce| pkce.code_verifier.clone()),
+            )
+            .request()
+            .await
+    }
+
+    /// 同时处理多个连接，浏览器预连接等不发送请求的连接不会阻塞回调
+    async fn accept_code(&self) -> crate::Result<String> {
+        let mut connections = FuturesUnordered::new();
+        loop {
+            let accept = std::pin::pin!(self.listener.accept());
+            let next = if connections.is_empty() {
+                Either::Left(accept.await)
+            } else {
+                match futures::future::select(accept, connections.next()).await {
+                    Either::Left((accepted, _)) => Either::Left(accepted),
+                    Either::Right((handled, _)) => Either::Right(handled),
+                }
+            };
+            match next {
+                Either::Left(accepted) => connections.push(self.handle_connection(accepted?.0)),
+                Either::Right(Some(Some(result))) => return result,
+                Either::Right(_) => {}
+            }
+        }
+    }
+
+    /// 返回 None 时继续等待下一个请求
+    async fn handle_connection(&self, mut stream: TcpStream) -> Option<crate::Result<String>> {
+        let target = match tokio::time::timeout(
+            REQUEST_READ_TIMEOUT,
+            read_request_target(&mut stream),
+        )
+        .await
+        {
+            Ok(Ok(target)) => target,
+            _ => return None,
+        };
+        // 浏览器可能还会请求 favicon 等其它路径
+        if target.split('?').next() != Some(self.login.callback_path.as_str()) {
+            write_response(&mut stream, "404 Not Found", "").await;
+            return None;
+        }
+        let callback = match OauthCallback::parse_url(target.as_str()) {
+            Ok(callback) => callback,
+            Err(_) => {
+                write_response(&mut stream, "400 Bad Request", OAUTH_LOOPBACK_FAILURE_HTML).await;
+                return None;
+            }
+        };
+        // state 不一致的请求不是这次授权的回调，可能是伪造的，继续等待；用户拒绝授权时可能没有 state
+        let denied = callback.error.is_some() && callback.state.is_none();
+        if !denied && callback.state.as_deref() != Some(self.state.as_str()) {
+            write_response(&mut stream, "400 Bad Request", OAUTH_LOOPBACK_FAILURE_HTML).await;
+            return None;
+        }
+        let result = callback.verify_state(self.state.as_str());
+        match &result {
+            Ok(_) => write_response(&mut stream, "200 OK", self.login.success_html.as_str()).await,
+            Err(_) => {
+                write_response(&mut stream, "400 Bad Request", OAUTH_LOOPBACK_FAILURE_HTML).await
+            }
+        }
+        Some(result)
+    }
+}
+
+/// 读取请求行和请求头，返回请求的路径
+async fn read_request_target(stream: &mut TcpStream) -> crate::Result<String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    read_line(&mut reader, &mut line).await?;
+    let mut request_line = line.split_whitespace();
+    let target = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) if target.starts_with('/') => target.to_string(),
+        _ => return Err(AlipanError::msg("invalid callback request")),
+    };
+    for _ in 0..MAX_REQUEST_HEADERS {
+        if read_line(&mut reader, &mut line).await? == 0 || line.trim_end().is_empty() {
+            return Ok(target);
+        }
+    }
+    Err(AlipanError::msg("too many headers in callback request"))
+}
+
+/// 读取一行，超过 MAX_REQUEST_LINE_LEN 时返回错误
+async fn read_line(
+    reader: &mut BufReader<&mut TcpStream>,
+    line: &mut String,
+) -> crate::Result<usize> {
+    line.clear();
+    let len = reader
+        .take(MAX_REQUEST_LINE_LEN as u64)
+        .read_line(line)
+        .await?;
+    if len == MAX_REQUEST_LINE_LEN && !line.ends_with('\n') {
+        return Err(AlipanError::msg("callback request line too long"));
+    }
+    Ok(len)
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, html: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        html.len(),
+        html
+    );
+    // 浏览器是否收到页面不影响授权结果
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
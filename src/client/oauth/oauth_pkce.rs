@@ -0,0 +1,39 @@
+use crate::AlipanError;
+use base64::Engine;
+use sha2::Digest;
+
+/// PKCE 参数，用于无法保存 client_secret 的客户端
+///
+/// code_challenge 放到授权链接中，code_verifier 在换取 AccessToken 时提交
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OauthPkce {
+    pub code_verifier: String,
+    pub code_challenge: String,
+    /// S256 或 plain
+    pub code_challenge_method: String,
+}
+
+impl OauthPkce {
+    /// 生成 32 字节随机数作为 code_verifier，使用 S256 计算 code_challenge
+    pub fn new() -> crate::Result<Self> {
+        Ok(Self::s256(random_url_safe(32)?))
+    }
+
+    pub fn s256(code_verifier: impl Into<String>) -> Self {
+        let code_verifier = code_verifier.into();
+        let digest = sha2::Sha256::digest(code_verifier.as_bytes());
+        Self {
+            code_challenge: base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest),
+            code_verifier,
+            code_challenge_method: "S256".to_string(),
+        }
+    }
+}
+
+/// 指定字节数的随机数，使用不带填充的 base64url 编码
+pub(crate) fn random_url_safe(len: usize) -> crate::Result<String> {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes)
+        .map_err(|err| AlipanError::msg(format!("failed to generate random bytes: {}", err)))?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+}
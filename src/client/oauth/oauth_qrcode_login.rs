@@ -0,0 +1,155 @@
+use crate::oauth_access_token::OauthAccessToken;
+use crate::{
+    AlipanError, ErrorInfo, GrantType, OAuthClient, OauthAuthorizeQrCode, OauthPkce,
+    OauthQrCodeStatus,
+};
+use std::time::Duration;
+use tokio::time::Instant;
+
+impl OAuthClient {
+    /// 扫码登录，适用于没有浏览器的设备
+    pub async fn oauth_qrcode_login(&self) -> OauthQrCodeLogin<'_> {
+        OauthQrCodeLogin {
+            client: self,
+            scopes: vec![],
+            width: None,
+            height: None,
+            pkce: None,
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OauthQrCodeLogin<'a> {
+    pub client: &'a OAuthClient,
+    pub scopes: Vec<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// 设置后使用 PKCE，不需要 client_secret
+    pub pkce: Option<OauthPkce>,
+    pub poll_interval: Duration,
+    /// 超过这个时间没有完成扫码时返回 QrCodeLoginTimeout
+    pub timeout: Duration,
+}
+
+impl<'a> OauthQrCodeLogin<'a> {
+    pub fn scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<Option<i64>>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn height(mut self, height: impl Into<Option<i64>>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    pub fn pkce(mut self, pkce: impl Into<Option<OauthPkce>>) -> Self {
+        self.pkce = pkce.into();
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 获取二维码，展示 qr_code_url 之后调用 OauthQrCodeSession::wait 等待扫码
+    pub async fn start(self) -> crate::Result<OauthQrCodeSession<'a>> {
+        let mut request = self
+            .client
+            .oauth_authorize_qrcode()
+            .await
+            .scopes(self.scopes.iter().map(String::as_str))
+            .width(self.width)
+            .height(self.height);
+        if let Some(pkce) = &self.pkce {
+            request = request.pkce(pkce);
+        }
+        let qrcode = request.request().await?;
+        Ok(OauthQrCodeSession {
+            qrcode,
+            deadline: Instant::now() + self.timeout,
+            login: self,
+        })
+    }
+}
+
+/// 已经生成的二维码
+#[derive(Debug)]
+pub struct OauthQrCodeSession<'a> {
+    pub login: OauthQrCodeLogin<'a>,
+    pub qrcode: OauthAuthorizeQrCode,
+    deadline: Instant,
+}
+
+impl<'a> OauthQrCodeSession<'a> {
+    /// 二维码图片的地址，用于展示给用户扫描
+    pub fn qr_code_url(&self) -> &str {
+        self.qrcode.qr_code_url.as_str()
+    }
+
+    pub fn sid(&self) -> &str {
+        self.qrcode.sid.as_str()
+    }
+
+    /// 轮询扫码状态，登录成功后使用返回的 authCode 换取 AccessToken
+    pub async fn wait(self) -> crate::Result<OauthAccessToken> {
+        let auth_code = loop {
+            let status = self
+                .login
+                .client
+                .oauth_qrcode_status()
+                .await
+                .sid(self.sid())
+                .request()
+                .await?;
+            match status.status {
+                OauthQrCodeStatus::LoginSuccess => {
+                    if let Some(auth_code) = status.auth_code {
+                        break auth_code;
+                    }
+                    return Err(AlipanError::msg("login success without auth code"));
+                }
+                OauthQrCodeStatus::QRCodeExpired => {
+                    return Err(AlipanError::new(ErrorInfo::QrCodeExpired(
+                        self.sid().to_string(),
+                    )))
+                }
+                OauthQrCodeStatus::WaitLogin | OauthQrCodeStatus::ScanSuccess => {}
+            }
+            let now = Instant::now();
+            if now >= self.deadline {
+                return Err(AlipanError::new(ErrorInfo::QrCodeLoginTimeout(
+                    self.sid().to_string(),
+                )));
+            }
+            tokio::time::sleep_until(self.deadline.min(now + self.login.poll_interval)).await;
+        };
+        self.login
+            .client
+            .oauth_access_token()
+            .await
+            .grant_type(GrantType::AuthorizationCode)
+            .code(auth_code)
+            .code_verifier(
+                self.login
+                    .pkce
+                    .as_ref()
+                    .map(|pkce| pkce.code_verifier.clone()),
+            )
+            .request()
+            .await
+    }
+}
@@ -0,0 +1,199 @@
+use crate::oauth_pkce::random_url_safe;
+use crate::{AlipanError, ErrorInfo, OauthPkce};
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 授权回调中的参数
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OauthCallback {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// 用户拒绝授权等情况下返回
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+impl OauthCallback {
+    /// 解析回调的完整链接，或者以 `/` 开头的路径
+    pub fn parse_url(url: &str) -> crate::Result<Self> {
+        let url = if url.starts_with('/') {
+            url::Url::parse(format!("http://127.0.0.1{}", url).as_str())?
+        } else {
+            url::Url::parse(url)?
+        };
+        Ok(Self::parse_query(url.query().unwrap_or_default()))
+    }
+
+    /// 解析 query 部分，不包含 `?`
+    pub fn parse_query(query: &str) -> Self {
+        let mut callback = Self::default();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let field = match key.as_ref() {
+                "code" => &mut callback.code,
+                "state" => &mut callback.state,
+                "error" => &mut callback.error,
+                "error_description" => &mut callback.error_description,
+                _ => continue,
+            };
+            field.get_or_insert_with(|| value.into_owned());
+        }
+        callback
+    }
+
+    /// 检查回调中的错误和 state，返回 code
+    pub fn verify_state(&self, expected_state: &str) -> crate::Result<String> {
+        self.check_error()?;
+        if self.state.as_deref() != Some(expected_state) {
+            return Err(AlipanError::new(ErrorInfo::OauthStateMismatch));
+        }
+        self.require_code()
+    }
+
+    fn check_error(&self) -> crate::Result<()> {
+        if let Some(error) = &self.error {
+            return Err(AlipanError::new(ErrorInfo::OauthCallbackError(
+                error.clone(),
+                self.error_description.clone(),
+            )));
+        }
+        Ok(())
+    }
+
+    fn require_code(&self) -> crate::Result<String> {
+        match &self.code {
+            Some(code) if !code.is_empty() => Ok(code.clone()),
+            _ => Err(AlipanError::require_param_missing("code")),
+        }
+    }
+}
+
+/// 保存在 OauthStateStore 中的数据
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct OauthStateData {
+    /// 过期时间，秒级时间戳
+    pub expires_at: i64,
+    /// 使用 PKCE 时换取 AccessToken 需要的 code_verifier
+    pub code_verifier: Option<String>,
+}
+
+/// state 的存储，多实例部署时可以使用 redis 等共享存储实现
+#[async_trait]
+pub trait OauthStateStore: Debug + Send + Sync {
+    async fn save_state(&self, state: String, data: OauthStateData) -> anyhow::Result<()>;
+
+    /// 取出并删除 state，每个 state 只能使用一次
+    async fn take_state(&self, state: &str) -> anyhow::Result<Option<OauthStateData>>;
+}
+
+/// 保存在内存中的 state，仅适用于单实例
+#[derive(Debug, Clone, Default)]
+pub struct MemoryOauthStateStore {
+    states: Arc<Mutex<HashMap<String, OauthStateData>>>,
+}
+
+#[async_trait]
+impl OauthStateStore for MemoryOauthStateStore {
+    async fn save_state(&self, state: String, data: OauthStateData) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let mut states = self.states.lock().unwrap();
+        // 没有回调的 state 在这里清理
+        states.retain(|_, data| data.expires_at > now);
+        states.insert(state, data);
+        Ok(())
+    }
+
+    async fn take_state(&self, state: &str) -> anyhow::Result<Option<OauthStateData>> {
+        Ok(self.states.lock().unwrap().remove(state))
+    }
+}
+
+/// 生成和校验授权链接中的 state，防止 CSRF
+#[derive(Debug, Clone)]
+pub struct OauthStateManager {
+    pub store: Arc<Box<dyn OauthStateStore>>,
+    /// state 的有效期，默认 10 分钟
+    pub ttl: Duration,
+}
+
+impl Default for OauthStateManager {
+    fn default() -> Self {
+        let store: Box<dyn OauthStateStore> = Box::new(MemoryOauthStateStore::default());
+        Self::new(store)
+    }
+}
+
+/// 校验通过的回调，可以用于换取 AccessToken
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OauthVerifiedCallback {
+    pub code: String,
+    pub state: String,
+    pub code_verifier: Option<String>,
+}
+
+impl OauthStateManager {
+    pub fn new(store: impl Into<Arc<Box<dyn OauthStateStore>>>) -> Self {
+        Self {
+            store: store.into(),
+            ttl: Duration::from_secs(600),
+        }
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// 生成一个新的 state，放到 OauthAuthorizeUrl::state 中
+    pub async fn generate(&self) -> crate::Result<String> {
+        self.save(None).await
+    }
+
+    /// 生成 state 和 PKCE，code_verifier 与 state 一起保存
+    pub async fn generate_with_pkce(&self) -> crate::Result<(String, OauthPkce)> {
+        let pkce = OauthPkce::new()?;
+        let state = self.save(Some(pkce.code_verifier.clone())).await?;
+        Ok((state, pkce))
+    }
+
+    /// 校验回调的链接，state 校验通过后失效
+    pub async fn verify_url(&self, url: &str) -> crate::Result<OauthVerifiedCallback> {
+        self.verify(&OauthCallback::parse_url(url)?).await
+    }
+
+    /// 校验回调的参数，state 校验通过后失效
+    pub async fn verify(&self, callback: &OauthCallback) -> crate::Result<OauthVerifiedCallback> {
+        let state = match &callback.state {
+            Some(state) if !state.is_empty() => state,
+            _ => return Err(AlipanError::new(ErrorInfo::OauthStateMismatch)),
+        };
+        // 带有错误的回调也消耗掉 state
+        let data = self.store.take_state(state).await?;
+        callback.check_error()?;
+        let data = match data {
+            Some(data) => data,
+            None => return Err(AlipanError::new(ErrorInfo::OauthStateMismatch)),
+        };
+        if data.expires_at <= chrono::Utc::now().timestamp() {
+            return Err(AlipanError::new(ErrorInfo::OauthStateExpired));
+        }
+        Ok(OauthVerifiedCallback {
+            code: callback.require_code()?,
+            state: state.clone(),
+            code_verifier: data.code_verifier,
+        })
+    }
+
+    async fn save(&self, code_verifier: Option<String>) -> crate::Result<String> {
+        let state = random_url_safe(16)?;
+        let data = OauthStateData {
+            expires_at: chrono::Utc::now().timestamp() + self.ttl.as_secs() as i64,
+            code_verifier,
+        };
+        self.store.save_state(state.clone(), data).await?;
+        Ok(state)
+    }
+}
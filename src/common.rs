@@ -75,6 +75,13 @@ enum_str!(GrantType {
     RefreshToken("refresh_token"),
 });
 
+enum_str!(OauthQrCodeStatus {
+    WaitLogin("WaitLogin"),
+    ScanSuccess("ScanSuccess"),
+    LoginSuccess("LoginSuccess"),
+    QRCodeExpired("QRCodeExpired"),
+});
+
 enum_str! (AdriveOpenFileType {
     File("file"),
     Folder("folder"),
@@ -86,6 +93,26 @@ enum_str!(CheckNameMode {
     Ignore("ignore"),
 });
 
+enum_str!(AdriveSearchField {
+    Name("name"),
+    FileExtension("file_extension"),
+    Category("category"),
+    Type("type"),
+    ParentFileId("parent_file_id"),
+    CreatedAt("created_at"),
+    UpdatedAt("updated_at"),
+});
+
+enum_str!(AdriveSearchOperator {
+    Eq("="),
+    Ne("!="),
+    Gt(">"),
+    Ge(">="),
+    Lt("<"),
+    Le("<="),
+    Match("match"),
+});
+
 enum_str!(AdriveAsyncTaskState {
     Succeed("Succeed"),
     Running("Running"),
@@ -1,3 +1,4 @@
+use crate::AdriveOpenFileCreate;
 use reqwest::StatusCode;
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "backtrace")]
@@ -64,6 +65,33 @@ pub enum ErrorInfo {
     Msg(String),
     RequireParamMissing(String),
     Anyhow(anyhow::Error),
+    FileExist(Box<AdriveOpenFileCreate>),
+    /// 分片上传地址已过期，值为分片序号
+    UploadUrlExpired(i64),
+    /// 下载地址已过期，值为分块的开始位置
+    DownloadUrlExpired(i64),
+    /// 传输被 CancellationToken 取消
+    Cancelled,
+    /// 下载的文件与 content_hash 不一致，值为 (期望的, 实际的)
+    ContentHashMismatch(String, String),
+    /// 路径不存在，值为请求的路径
+    PathNotFound(String),
+    /// 异步任务执行失败，值为 async_task_id
+    AsyncTaskFailed(String),
+    /// 等待异步任务超时，值为 async_task_id
+    AsyncTaskTimeout(String),
+    /// 二维码已过期，值为 sid
+    QrCodeExpired(String),
+    /// 等待扫码登录超时，值为 sid
+    QrCodeLoginTimeout(String),
+    /// 授权回调的 state 与发起授权时的不一致，或者已经使用过
+    OauthStateMismatch,
+    /// 授权回调的 state 已过期
+    OauthStateExpired,
+    /// 授权回调中带有错误，值为 (error, error_description)
+    OauthCallbackError(String, Option<String>),
+    /// 等待授权回调超时
+    OauthLoginTimeout,
 }
 
 impl Display for ErrorInfo {
@@ -76,7 +104,7 @@ impl Display for ErrorInfo {
 pub struct ServerError {
     pub code: String,
     pub message: String,
-    #[serde(rename = "requestId")]
+    #[serde(default, rename = "requestId")]
     pub request_id: String,
 }
 
@@ -1,8 +1,9 @@
 use crate::client::common::access_token_loader::AccessToken;
 use crate::{
     AdriveClient, AdriveOpenFileBatchGetRequestFileList, AdriveOpenFilePartInfoCreate,
-    AdriveOpenFileType, BoxedAccessTokenLoader, CheckNameMode, GrantType, OAuthClient,
-    OAuthClientAccessTokenManager, OAuthClientAccessTokenStore,
+    AdriveOpenFileType, AdriveUploadSession, AdriveUploadSessionStore, AlipanError,
+    BoxedAccessTokenLoader, CheckNameMode, GrantType, OAuthClient, OAuthClientAccessTokenManager,
+    OAuthClientAccessTokenStore,
 };
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
@@ -54,10 +55,12 @@ impl OAuthClientAccessTokenStore for FileAccessTokenStore {
 const ACCESS_TOKEN_JSON_PATH: &str = "target/access_token.json";
 
 async fn access_token_loader() -> BoxedAccessTokenLoader {
-    Box::new(OAuthClientAccessTokenManager {
-        oauth_client: Arc::new(oauth_client().await),
-        access_token_store: Arc::new(Box::new(FileAccessTokenStore::new(ACCESS_TOKEN_JSON_PATH))),
-    })
+    let access_token_store: Box<dyn OAuthClientAccessTokenStore> =
+        Box::new(FileAccessTokenStore::new(ACCESS_TOKEN_JSON_PATH));
+    Box::new(OAuthClientAccessTokenManager::new(
+        oauth_client().await,
+        access_token_store,
+    ))
 }
 
 // 构建客户端
@@ -299,6 +302,53 @@ fn sha1(bytes: &[u8]) -> String {
     hex::encode(result)
 }
 
+#[tokio::test]
+async fn test_adrive_rapid_upload_hash() -> anyhow::Result<()> {
+    use crate::{
+        adrive_content_hash, adrive_pre_hash, adrive_proof_code, adrive_proof_range,
+        adrive_source_content_hash, adrive_source_pre_hash, adrive_source_proof_code,
+        BoxedAdriveUploadSource, ReaderUploadSource,
+    };
+    const ACCESS_TOKEN: &str = "eyJhbGciOiJSUzI1NiJ9.test-access-token";
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+    assert_eq!(
+        adrive_pre_hash(&data),
+        "0ac28084ff74933d05123496dafd3791684d9b53"
+    );
+    assert_eq!(
+        adrive_content_hash(&data),
+        "e6937aa016bc06eda81d3b227fffde825d881ce5"
+    );
+    assert_eq!(
+        adrive_proof_range(ACCESS_TOKEN, data.len() as i64),
+        (4419, 4427)
+    );
+    assert_eq!(adrive_proof_code(ACCESS_TOKEN, &data), "mJmam5ydnp8=");
+    // 不足 8 个字节时取到文件末尾
+    assert_eq!(adrive_proof_range(ACCESS_TOKEN, TEXT.len() as i64), (5, 13));
+    assert_eq!(
+        adrive_proof_code(ACCESS_TOKEN, TEXT.as_bytes()),
+        "LCBXb3JsZCE="
+    );
+    assert_eq!(adrive_proof_code(ACCESS_TOKEN, &[]), "");
+    // 从 source 计算结果一致
+    let source: BoxedAdriveUploadSource =
+        Box::new(ReaderUploadSource::new(std::io::Cursor::new(data.clone())));
+    assert_eq!(
+        adrive_source_pre_hash(&source).await?,
+        adrive_pre_hash(&data)
+    );
+    assert_eq!(
+        adrive_source_content_hash(&source).await?,
+        adrive_content_hash(&data)
+    );
+    assert_eq!(
+        adrive_source_proof_code(ACCESS_TOKEN, &source).await?,
+        adrive_proof_code(ACCESS_TOKEN, &data)
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_adrive_open_file_create_file_upload() -> anyhow::Result<()> {
     let open_file_create = crate::tests::client()
@@ -341,9 +391,7 @@ async fn test_adrive_open_file_create_large_upload() -> anyhow::Result<()> {
     let part_count = size / part_size + if size % part_size == 0 { 0 } else { 1 };
     // 创建文件
     let parts = (1..=part_count)
-        .map(|i| AdriveOpenFilePartInfoCreate {
-            part_number: i as i64,
-        })
+        .map(|i| AdriveOpenFilePartInfoCreate { part_number: i })
         .collect::<Vec<_>>();
     let open_file_create = client
         .adrive_open_file_create()
@@ -421,6 +469,44 @@ async fn test_adrive_open_file_create_large_upload() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_adrive_uploader() -> anyhow::Result<()> {
+    let open_file_complete = client()
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id(drive_id().await?)
+        .parent_file_id("root")
+        .file_path(LARGE_FILE_PATH)
+        .upload()
+        .await?;
+    println!("{}", serde_json::to_string(&open_file_complete)?);
+    Ok(())
+}
+
+#[test]
+fn test_adrive_upload_part_size() -> anyhow::Result<()> {
+    use crate::{
+        adrive_upload_part_count, adrive_upload_part_size, ADRIVE_UPLOAD_DEFAULT_PART_SIZE,
+        ADRIVE_UPLOAD_MAX_PART_COUNT, ADRIVE_UPLOAD_MIN_PART_SIZE,
+    };
+    // 小文件使用最小分片
+    assert_eq!(adrive_upload_part_size(0, 1)?, ADRIVE_UPLOAD_MIN_PART_SIZE);
+    assert_eq!(adrive_upload_part_count(0, ADRIVE_UPLOAD_MIN_PART_SIZE), 1);
+    // 默认分片
+    let size = 100 << 20;
+    let part_size = adrive_upload_part_size(size, ADRIVE_UPLOAD_DEFAULT_PART_SIZE)?;
+    assert_eq!(part_size, ADRIVE_UPLOAD_DEFAULT_PART_SIZE);
+    assert_eq!(adrive_upload_part_count(size, part_size), 7);
+    // 分片数不超过 10000
+    let size = 1 << 40;
+    let part_size = adrive_upload_part_size(size, ADRIVE_UPLOAD_DEFAULT_PART_SIZE)?;
+    assert!(adrive_upload_part_count(size, part_size) <= ADRIVE_UPLOAD_MAX_PART_COUNT);
+    // 超过 5GB * 10000
+    assert!(adrive_upload_part_size(60 << 40, ADRIVE_UPLOAD_DEFAULT_PART_SIZE).is_err());
+    Ok(())
+}
+
 async fn put_file(url: &str, path: &str, skip: i64, read_len: i64) -> anyhow::Result<()> {
     let (sender, body) = PutResource::channel_resource();
     let request = reqwest::Client::new().put(url).body(body).send();
@@ -617,6 +703,21 @@ async fn test_adrive_open_file_recyclebin_trash() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_adrive_open_file_recyclebin_restore() -> anyhow::Result<()> {
+    let open_file_restore = crate::tests::client()
+        .await
+        .adrive_open_file_recyclebin_restore()
+        .await
+        .drive_id(crate::tests::drive_id().await?)
+        .file_id("file_id".to_string())
+        .request()
+        .await?;
+    println!("{:?}", open_file_restore);
+    println!("{}", serde_json::to_string(&open_file_restore)?);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_adrive_open_file_delete() -> anyhow::Result<()> {
     let client = client().await;
@@ -656,6 +757,7 @@ impl PutResource {
     }
 }
 
+#[allow(dead_code)]
 impl PutResource {
     pub async fn file_resource(path: &str) -> crate::Result<Body> {
         let file = tokio::fs::read(path).await?;
@@ -678,3 +780,2673 @@ fn test_time() {
     let s = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
     println!("{}", s);
 }
+
+// 本地模拟服务器，用于不需要真实账号的测试
+
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+}
+
+pub type MockHandler = Arc<dyn Fn(MockRequest) -> (u16, String) + Send + Sync>;
+
+/// 启动本地 HTTP 服务器，每个连接只处理一个请求，返回 http://127.0.0.1:port
+pub async fn mock_server(handler: MockHandler) -> anyhow::Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let _ = mock_serve(stream, handler).await;
+            });
+        }
+    });
+    Ok(format!("http://{}", addr))
+}
+
+async fn mock_serve(stream: tokio::net::TcpStream, handler: MockHandler) -> anyhow::Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+    let mut reader = tokio::io::BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let mut headers = vec![];
+    loop {
+        line.clear();
+        reader.read_line(&mut line).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+    let mut request = MockRequest {
+        method,
+        path,
+        headers,
+        body: vec![],
+    };
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            line.clear();
+            reader.read_line(&mut line).await?;
+            let len = usize::from_str_radix(line.trim(), 16)?;
+            let mut chunk = vec![0u8; len + 2];
+            reader.read_exact(&mut chunk).await?;
+            if len == 0 {
+                break;
+            }
+            request.body.extend_from_slice(&chunk[..len]);
+        }
+    } else if let Some(len) = request.header("content-length") {
+        let mut body = vec![0u8; len.parse()?];
+        reader.read_exact(&mut body).await?;
+        request.body = body;
+    }
+    let (code, body) = handler(request);
+    let response = format!(
+        "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct StaticAccessTokenLoader;
+
+#[async_trait]
+impl crate::AccessTokenLoader for StaticAccessTokenLoader {
+    async fn get_access_token(&self) -> anyhow::Result<AccessToken> {
+        Ok(AccessToken {
+            access_token: "access_token".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: 7200,
+            refresh_token: "refresh_token".to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        })
+    }
+}
+
+async fn mock_client(api_host: &str) -> AdriveClient {
+    AdriveClient::default()
+        .set_api_host(api_host)
+        .await
+        .set_access_token_loader(Box::new(StaticAccessTokenLoader))
+        .await
+}
+
+const MOCK_COMPLETE: &str = r#"{"drive_id":"1","file_id":"f1","name":"mock.bin","size":0,"file_extension":"bin","content_hash":"","category":"others","type":"file","created_at":"2024-01-01T00:00:00.000Z","updated_at":"2024-01-01T00:00:00.000Z"}"#;
+
+/// 模拟 create / getUploadUrl / 分片上传 / complete
+///
+/// 第一次上传第 2 个分片时返回 500，第 3 个分片第一次获取的上传地址已过期
+fn mock_upload_handler(
+    parts: Arc<std::sync::Mutex<std::collections::BTreeMap<i64, Vec<u8>>>>,
+    calls: Arc<std::sync::Mutex<Vec<String>>>,
+) -> MockHandler {
+    let failed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let issued = Arc::new(std::sync::Mutex::new(
+        std::collections::HashMap::<i64, i64>::new(),
+    ));
+    Arc::new(move |request: MockRequest| {
+        calls.lock().unwrap().push(request.path.clone());
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/create" => {
+                let part_info_list = request.json()["part_info_list"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|part| serde_json::json!({"part_number": part["part_number"]}))
+                    .collect::<Vec<_>>();
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "file_id": "f1",
+                    "parent_file_id": "root",
+                    "upload_id": "u1",
+                    "file_name": "mock.bin",
+                    "available": true,
+                    "exist": false,
+                    "rapid_upload": false,
+                    "part_info_list": part_info_list,
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/getUploadUrl" => {
+                let part_info_list = request.json()["part_info_list"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|part| {
+                        let part_number = part["part_number"].as_i64().unwrap();
+                        let mut issued = issued.lock().unwrap();
+                        let version = issued.entry(part_number).or_default();
+                        *version += 1;
+                        let host = request.header("host").unwrap_or_default();
+                        let upload_url =
+                            format!("http://{}/upload/{}?v={}", host, part_number, version);
+                        serde_json::json!({"part_number": part_number, "upload_url": upload_url})
+                    })
+                    .collect::<Vec<_>>();
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "file_id": "f1",
+                    "upload_id": "u1",
+                    "part_info_list": part_info_list,
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/listUploadedParts" => {
+                // 每页 3 个分片
+                let marker: i64 = request.json()["part_number_marker"]
+                    .as_str()
+                    .unwrap_or("0")
+                    .parse()
+                    .unwrap();
+                let parts = parts.lock().unwrap();
+                let page = parts
+                    .keys()
+                    .filter(|part_number| **part_number > marker)
+                    .take(3)
+                    .map(|part_number| serde_json::json!({"part_number": part_number, "part_size": 1}))
+                    .collect::<Vec<_>>();
+                let next = if page.len() == 3 {
+                    page[2]["part_number"].to_string()
+                } else {
+                    "".to_string()
+                };
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "upload_id": "u1",
+                    "parallelUpload": false,
+                    "uploaded_parts": page,
+                    "next_part_number_marker": next,
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/complete" => (200, MOCK_COMPLETE.to_string()),
+            path if path.starts_with("/upload/") && request.method == "PUT" => {
+                let (part_number, version) = path["/upload/".len()..].split_once("?v=").unwrap();
+                let part_number: i64 = part_number.parse().unwrap();
+                if part_number == 3 && version == "1" {
+                    let body = "<Error><Code>AccessDenied</Code><Message>Request has expired.</Message></Error>";
+                    return (403, body.to_string());
+                }
+                if part_number == 2 && !failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                    return (500, "".to_string());
+                }
+                parts.lock().unwrap().insert(part_number, request.body);
+                (200, "".to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_concurrency() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(1000 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    let cache = crate::AdrivePathCache::new(std::time::Duration::from_secs(60));
+    let complete = mock_client(api_host.as_str())
+        .await
+        .set_path_cache(cache.clone())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .part_size(100 << 10)
+        .rapid_upload(false)
+        .concurrency(4)
+        .upload_with_outcome()
+        .await?;
+    assert!(!complete.rapid_upload);
+    let complete = complete.complete;
+    assert_eq!(complete.file_id, "f1");
+    // 上传完成的文件写入路径缓存
+    assert_eq!(cache.file_id("1", "/mock.bin").as_deref(), Some("f1"));
+    let parts = parts.lock().unwrap();
+    assert_eq!(parts.len(), 10);
+    assert_eq!(parts.values().flatten().copied().collect::<Vec<_>>(), data);
+    let calls = calls.lock().unwrap();
+    let get_upload_url_calls = calls
+        .iter()
+        .filter(|path| path.ends_with("getUploadUrl"))
+        .count();
+    // 一次批量获取，加上第 3 个分片过期后重新获取
+    assert_eq!(get_upload_url_calls, 2);
+    assert!(calls.iter().any(|path| path == "/upload/3?v=2"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_reader_parallel_puts() -> anyhow::Result<()> {
+    // 接收缓冲区很小，服务端不读取时分片无法发送完
+    let socket = tokio::net::TcpSocket::new_v4()?;
+    socket.set_recv_buffer_size(4 << 10)?;
+    socket.bind("127.0.0.1:0".parse()?)?;
+    let listener = socket.listen(16)?;
+    let api_host = format!("http://{}", listener.local_addr()?);
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let handler = mock_upload_handler(parts.clone(), Arc::new(std::sync::Mutex::new(vec![])));
+    let (puts, _) = tokio::sync::watch::channel(0usize);
+    let puts = Arc::new(puts);
+    let parallel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let server_puts = puts.clone();
+    let server_parallel = parallel.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            let puts = server_puts.clone();
+            let parallel = server_parallel.clone();
+            tokio::spawn(async move {
+                let mut method = [0u8; 4];
+                let _ = stream.peek(&mut method).await;
+                if &method == b"PUT " {
+                    // 两个 PUT 都在等待读取时才开始读取数据
+                    puts.send_modify(|puts| *puts += 1);
+                    let mut receiver = puts.subscribe();
+                    let started = tokio::time::timeout(
+                        std::time::Duration::from_secs(3),
+                        receiver.wait_for(|puts| *puts >= 2),
+                    )
+                    .await
+                    .is_ok_and(|started| started.is_ok());
+                    puts.send_modify(|puts| *puts -= 1);
+                    if started {
+                        parallel.store(true, std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+                let _ = mock_serve(stream, handler).await;
+            });
+        }
+    });
+    let data = (0..(16 << 20))
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<u8>>();
+    mock_client(api_host.as_str())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .part_size(8 << 20)
+        .rapid_upload(false)
+        .concurrency(2)
+        .upload()
+        .await?;
+    assert!(parallel.load(std::sync::atomic::Ordering::SeqCst));
+    let parts = parts.lock().unwrap();
+    assert_eq!(parts.values().flatten().copied().collect::<Vec<_>>(), data);
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryUploadSessionStore(
+    std::sync::Mutex<std::collections::HashMap<String, AdriveUploadSession>>,
+);
+
+#[async_trait]
+impl AdriveUploadSessionStore for MemoryUploadSessionStore {
+    async fn get_session(&self, key: &str) -> anyhow::Result<Option<AdriveUploadSession>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set_session(&self, key: &str, session: AdriveUploadSession) -> anyhow::Result<()> {
+        self.0.lock().unwrap().insert(key.to_string(), session);
+        Ok(())
+    }
+
+    async fn remove_session(&self, key: &str) -> anyhow::Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_resume() -> anyhow::Result<()> {
+    let data = (0..(1000 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    // 服务端已经有前 5 个分片
+    let parts = Arc::new(std::sync::Mutex::new(
+        (1..=5)
+            .map(|part_number: i64| {
+                let offset = ((part_number - 1) * (100 << 10)) as usize;
+                (part_number, data[offset..offset + (100 << 10)].to_vec())
+            })
+            .collect::<std::collections::BTreeMap<_, _>>(),
+    ));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let session_store: Arc<Box<dyn AdriveUploadSessionStore>> =
+        Arc::new(Box::new(MemoryUploadSessionStore::default()));
+    let session_key = crate::adrive_upload_session_key("1", "root", "mock.bin");
+    session_store
+        .set_session(
+            &session_key,
+            AdriveUploadSession {
+                drive_id: "1".to_string(),
+                file_id: "f1".to_string(),
+                upload_id: "u1".to_string(),
+                size: data.len() as i64,
+                part_size: 100 << 10,
+                fingerprint: format!("sha1:{}", crate::adrive_content_hash(&data)),
+            },
+        )
+        .await?;
+    mock_client(api_host.as_str())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .rapid_upload(false)
+        .session_store(session_store.clone())
+        .upload()
+        .await?;
+    assert_eq!(
+        parts
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>(),
+        data
+    );
+    assert!(session_store.get_session(&session_key).await?.is_none());
+    let calls = calls.lock().unwrap();
+    assert!(!calls.iter().any(|path| path.ends_with("create")));
+    assert_eq!(
+        calls
+            .iter()
+            .filter(|path| path.starts_with("/upload/"))
+            .count(),
+        5
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_resume_fingerprint_mismatch() -> anyhow::Result<()> {
+    let data = (0..(300 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    let other = vec![0u8; data.len()];
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let session_store: Arc<Box<dyn AdriveUploadSessionStore>> =
+        Arc::new(Box::new(MemoryUploadSessionStore::default()));
+    let session_key = crate::adrive_upload_session_key("1", "root", "mock.bin");
+    // 同样长度的另一份数据留下的会话
+    session_store
+        .set_session(
+            &session_key,
+            AdriveUploadSession {
+                drive_id: "1".to_string(),
+                file_id: "f0".to_string(),
+                upload_id: "u0".to_string(),
+                size: other.len() as i64,
+                part_size: 100 << 10,
+                fingerprint: format!("sha1:{}", crate::adrive_content_hash(&other)),
+            },
+        )
+        .await?;
+    // 指纹需要完整读取数据，读取和保存会话只计算一次
+    #[derive(Debug)]
+    struct CountingSource(
+        crate::BytesUploadSource,
+        Arc<std::sync::atomic::AtomicUsize>,
+    );
+
+    #[async_trait]
+    impl crate::AdriveUploadSource for CountingSource {
+        async fn size(&self) -> crate::Result<i64> {
+            self.0.size().await
+        }
+
+        async fn read_range(
+            &self,
+            offset: i64,
+            len: i64,
+        ) -> crate::Result<crate::AdriveUploadBody> {
+            self.0.read_range(offset, len).await
+        }
+
+        async fn fingerprint(&self) -> crate::Result<Option<String>> {
+            self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.0.fingerprint().await
+        }
+    }
+
+    let fingerprints = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let client = mock_client(api_host.as_str()).await;
+    client
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .source(Box::new(CountingSource(
+            crate::BytesUploadSource::new(data.clone()),
+            fingerprints.clone(),
+        )))
+        .part_size(100 << 10)
+        .rapid_upload(false)
+        .session_store(session_store.clone())
+        .upload()
+        .await?;
+    assert_eq!(fingerprints.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|path| path.ends_with("create")));
+    assert_eq!(
+        parts
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>(),
+        data
+    );
+
+    // 没有指纹的数据不保存会话
+    #[derive(Debug)]
+    struct NoFingerprintSource(crate::BytesUploadSource);
+
+    #[async_trait]
+    impl crate::AdriveUploadSource for NoFingerprintSource {
+        async fn size(&self) -> crate::Result<i64> {
+            self.0.size().await
+        }
+
+        async fn read_range(
+            &self,
+            offset: i64,
+            len: i64,
+        ) -> crate::Result<crate::AdriveUploadBody> {
+            self.0.read_range(offset, len).await
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingSessionStore(Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl AdriveUploadSessionStore for RecordingSessionStore {
+        async fn get_session(&self, _: &str) -> anyhow::Result<Option<AdriveUploadSession>> {
+            Ok(None)
+        }
+
+        async fn set_session(&self, _: &str, _: AdriveUploadSession) -> anyhow::Result<()> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn remove_session(&self, _: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let saved = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let session_store: Arc<Box<dyn AdriveUploadSessionStore>> =
+        Arc::new(Box::new(RecordingSessionStore(saved.clone())));
+    client
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .source(Box::new(NoFingerprintSource(
+            crate::BytesUploadSource::new(data.clone()),
+        )))
+        .part_size(100 << 10)
+        .rapid_upload(false)
+        .session_store(session_store)
+        .upload()
+        .await?;
+    assert_eq!(saved.load(std::sync::atomic::Ordering::SeqCst), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_rapid_upload() -> anyhow::Result<()> {
+    let data = (0..5000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+    let expected = (
+        crate::adrive_content_hash(&data),
+        crate::adrive_proof_code("access_token", &data),
+    );
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_calls = calls.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        let json = request.json();
+        handler_calls.lock().unwrap().push(json.clone());
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/create" if json["pre_hash"].is_string() => (
+                409,
+                r#"{"code":"PreHashMatched","message":"Pre hash matched."}"#.to_string(),
+            ),
+            "/adrive/v1.0/openFile/create" => {
+                if json["content_hash"] != expected.0.as_str()
+                    || json["proof_code"] != expected.1.as_str()
+                    || json["proof_version"] != "v1"
+                {
+                    return (400, "".to_string());
+                }
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "file_id": "f1",
+                    "parent_file_id": "root",
+                    "upload_id": "u1",
+                    "file_name": "mock.bin",
+                    "available": true,
+                    "exist": false,
+                    "rapid_upload": true,
+                    "part_info_list": [],
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/complete" => (200, MOCK_COMPLETE.to_string()),
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let outcome = mock_client(api_host.as_str())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data))
+        .upload_with_outcome()
+        .await?;
+    assert!(outcome.rapid_upload);
+    assert_eq!(outcome.complete.file_id, "f1");
+    // pre_hash 检查、秒传、完成，没有上传分片
+    assert_eq!(calls.lock().unwrap().len(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_stream() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(250 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    // 只实现 AsyncRead，不知道长度
+    let (mut writer, reader) = tokio::io::duplex(1 << 10);
+    let write_data = data.clone();
+    tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+        writer.write_all(&write_data).await?;
+        writer.shutdown().await
+    });
+    let client = mock_client(api_host.as_str()).await;
+    let uploader = client
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .part_size(100 << 10)
+        .concurrency(2);
+    let upload = uploader.upload_stream(reader);
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+    assert_send(upload).await?;
+    let parts = parts.lock().unwrap();
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts.values().flatten().copied().collect::<Vec<_>>(), data);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_stream_slow_reader() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(250 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    let (mut writer, reader) = tokio::io::duplex(1 << 10);
+    let write_data = data.clone();
+    let write_parts = parts.clone();
+    // 写完第一个分片后，等它上传完成再继续写入
+    let writer = tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+        writer.write_all(&write_data[..100 << 10]).await?;
+        let mut uploaded = false;
+        for _ in 0..500 {
+            if write_parts.lock().unwrap().contains_key(&1) {
+                uploaded = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        writer.write_all(&write_data[100 << 10..]).await?;
+        writer.shutdown().await?;
+        std::io::Result::Ok(uploaded)
+    });
+    mock_client(api_host.as_str())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .part_size(100 << 10)
+        .concurrency(4)
+        .upload_stream(reader)
+        .await?;
+    assert!(
+        writer.await??,
+        "part 1 was not uploaded while reading part 2"
+    );
+    let parts = parts.lock().unwrap();
+    assert_eq!(parts.values().flatten().copied().collect::<Vec<_>>(), data);
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct MockProgress {
+    total: std::sync::Mutex<Option<Option<i64>>>,
+    bytes: std::sync::atomic::AtomicU64,
+    discarded: std::sync::atomic::AtomicU64,
+    parts: std::sync::Mutex<Vec<i64>>,
+    retries: std::sync::atomic::AtomicUsize,
+    /// 第一个分片完成后取消
+    cancel: Option<crate::CancellationToken>,
+}
+
+impl crate::TransferProgress for MockProgress {
+    fn on_start(&self, total: Option<i64>) {
+        *self.total.lock().unwrap() = Some(total);
+    }
+
+    fn on_bytes(&self, bytes: u64) {
+        self.bytes
+            .fetch_add(bytes, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    fn on_part_completed(&self, part_number: i64, _len: i64) {
+        self.parts.lock().unwrap().push(part_number);
+        if let Some(cancel) = &self.cancel {
+            cancel.cancel();
+        }
+    }
+
+    fn on_retry(&self, _part_number: i64, _retry: usize, discarded: u64, _error: &AlipanError) {
+        self.retries
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.discarded
+            .fetch_add(discarded, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_progress() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(1000 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    let progress = Arc::new(MockProgress::default());
+    let boxed: Arc<crate::BoxedTransferProgress> = Arc::new(Box::new(progress.clone()));
+    mock_client(api_host.as_str())
+        .await
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .part_size(100 << 10)
+        .rapid_upload(false)
+        .concurrency(4)
+        .progress(boxed)
+        .upload()
+        .await?;
+    use std::sync::atomic::Ordering;
+    assert_eq!(
+        *progress.total.lock().unwrap(),
+        Some(Some(data.len() as i64))
+    );
+    let mut completed = progress.parts.lock().unwrap().clone();
+    completed.sort();
+    assert_eq!(completed, (1..=10).collect::<Vec<_>>());
+    // 第 2 个分片失败一次，第 3 个分片地址过期一次
+    assert_eq!(progress.retries.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        progress.bytes.load(Ordering::SeqCst) - progress.discarded.load(Ordering::SeqCst),
+        data.len() as u64
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_cancel() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(1000 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    let session_store: Arc<Box<dyn AdriveUploadSessionStore>> =
+        Arc::new(Box::new(MemoryUploadSessionStore::default()));
+    let session_key = crate::adrive_upload_session_key("1", "root", "mock.bin");
+    let cancellation_token = crate::CancellationToken::new();
+    let progress = Arc::new(MockProgress {
+        cancel: Some(cancellation_token.clone()),
+        ..Default::default()
+    });
+    let client = mock_client(api_host.as_str()).await;
+    let result = client
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .part_size(100 << 10)
+        .rapid_upload(false)
+        .session_store(session_store.clone())
+        .progress(Arc::new(Box::new(progress) as crate::BoxedTransferProgress))
+        .cancellation_token(cancellation_token)
+        .upload()
+        .await;
+    assert!(matches!(
+        result,
+        Err(AlipanError {
+            inner: crate::ErrorInfo::Cancelled,
+            ..
+        })
+    ));
+    let uploaded = parts.lock().unwrap().len();
+    assert!(uploaded < 10);
+    // 会话仍然保存着，再次上传时续传剩余的分片
+    assert!(session_store.get_session(&session_key).await?.is_some());
+    client
+        .adrive_uploader()
+        .await
+        .drive_id("1")
+        .name("mock.bin")
+        .reader(std::io::Cursor::new(data.clone()))
+        .rapid_upload(false)
+        .session_store(session_store.clone())
+        .upload()
+        .await?;
+    assert_eq!(
+        parts
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>(),
+        data
+    );
+    assert_eq!(
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.ends_with("create"))
+            .count(),
+        1
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_uploader_rate_limit() -> anyhow::Result<()> {
+    let parts = Arc::new(std::sync::Mutex::new(std::collections::BTreeMap::new()));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_upload_handler(parts.clone(), calls.clone())).await?;
+    let data = (0..(150 << 10)).map(|i| i as u8).collect::<Vec<u8>>();
+    // 两个上传共享 200KB/s
+    let client = mock_client(api_host.as_str())
+        .await
+        .set_rate_limiter(crate::RateLimiter::new(200 << 10))
+        .await;
+    let upload = |name: &'static str| {
+        let client = &client;
+        let data = data.clone();
+        async move {
+            client
+                .adrive_uploader()
+                .await
+                .drive_id("1")
+                .name(name)
+                .reader(std::io::Cursor::new(data))
+                .part_size(1 << 20)
+                .rapid_upload(false)
+                .upload()
+                .await
+        }
+    };
+    let start = std::time::Instant::now();
+    let (a, b) = tokio::join!(upload("a.bin"), upload("b.bin"));
+    a?;
+    b?;
+    let elapsed = start.elapsed();
+    // 共 300KB，最后一块不需要等待，至少需要约 (300KB - 16KB) / 200KB/s
+    assert!(
+        elapsed >= std::time::Duration::from_millis(1300),
+        "{:?}",
+        elapsed
+    );
+    assert!(elapsed < std::time::Duration::from_secs(5), "{:?}", elapsed);
+    Ok(())
+}
+
+fn mock_file_json(size: usize, content_hash: &str) -> serde_json::Value {
+    serde_json::json!({
+        "drive_id": "1",
+        "file_id": "f1",
+        "parent_file_id": "root",
+        "name": "mock.txt",
+        "size": size,
+        "file_extension": "txt",
+        "content_hash": content_hash,
+        "category": "doc",
+        "type": "file",
+        "created_at": "2024-01-01T00:00:00.000Z",
+        "updated_at": "2024-01-01T00:00:00.000Z",
+    })
+}
+
+/// 模拟 get / getDownloadUrl / Range 下载
+///
+/// 第一次获取的下载地址已经过期，offset 为 20KB 的分块第一次返回 500
+fn mock_download_handler(
+    data: Arc<Vec<u8>>,
+    content_hash: String,
+    calls: Arc<std::sync::Mutex<Vec<String>>>,
+) -> MockHandler {
+    let failed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let issued = Arc::new(std::sync::atomic::AtomicI64::new(0));
+    Arc::new(move |request: MockRequest| {
+        calls.lock().unwrap().push(request.path.clone());
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/get" => {
+                (200, mock_file_json(data.len(), &content_hash).to_string())
+            }
+            "/adrive/v1.0/openFile/getDownloadUrl" => {
+                let version = issued.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                let host = request.header("host").unwrap_or_default();
+                let expiration = if version == 1 {
+                    "2000-01-01T00:00:00.000Z"
+                } else {
+                    "2099-01-01T00:00:00.000Z"
+                };
+                let body = serde_json::json!({
+                    "url": format!("http://{}/download?v={}", host, version),
+                    "expiration": expiration,
+                    "method": "GET",
+                });
+                (200, body.to_string())
+            }
+            path if path.starts_with("/download?v=") && request.method == "GET" => {
+                if path == "/download?v=1" {
+                    return (403, "Request has expired.".to_string());
+                }
+                let range = request.header("range").unwrap_or_default();
+                let (start, end) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+                let start: usize = start.parse().unwrap();
+                let end: usize = end.parse().unwrap();
+                if start == 20 << 10 && !failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                    return (500, "".to_string());
+                }
+                let body =
+                    String::from_utf8(data[start..=end.min(data.len() - 1)].to_vec()).unwrap();
+                (206, body)
+            }
+            _ => (404, "".to_string()),
+        }
+    })
+}
+
+fn mock_download_data() -> Arc<Vec<u8>> {
+    Arc::new((0..(100 << 10)).map(|i| b'a' + (i % 26) as u8).collect())
+}
+
+#[tokio::test]
+async fn test_adrive_downloader() -> anyhow::Result<()> {
+    let data = mock_download_data();
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_download_handler(
+        data.clone(),
+        crate::adrive_content_hash(&data).to_uppercase(),
+        calls.clone(),
+    ))
+    .await?;
+    let file_path = std::env::temp_dir().join("alipan_test_adrive_downloader.txt");
+    let progress = Arc::new(MockProgress::default());
+    let client = mock_client(api_host.as_str()).await;
+    let downloader = client
+        .adrive_downloader()
+        .await
+        .drive_id("1")
+        .file_id("f1")
+        .file_path(&file_path)
+        .chunk_size(10 << 10)
+        .concurrency(4)
+        .progress(Arc::new(
+            Box::new(progress.clone()) as crate::BoxedTransferProgress
+        ));
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+    let file = assert_send(downloader.download()).await?;
+    assert_eq!(file.size, data.len() as i64);
+    assert_eq!(tokio::fs::read(&file_path).await?, *data);
+    let mut completed = progress.parts.lock().unwrap().clone();
+    completed.sort();
+    assert_eq!(completed, (1..=10).collect::<Vec<_>>());
+    assert_eq!(
+        progress.retries.load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+    tokio::fs::remove_file(&file_path).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_downloader_content_hash_mismatch() -> anyhow::Result<()> {
+    let data = mock_download_data();
+    let api_host = mock_server(mock_download_handler(
+        data.clone(),
+        crate::adrive_content_hash(b"other"),
+        Arc::new(std::sync::Mutex::new(vec![])),
+    ))
+    .await?;
+    let file_path = std::env::temp_dir().join("alipan_test_adrive_downloader_mismatch.txt");
+    let result = mock_client(api_host.as_str())
+        .await
+        .adrive_downloader()
+        .await
+        .drive_id("1")
+        .file_id("f1")
+        .file_path(&file_path)
+        .chunk_size(50 << 10)
+        .download()
+        .await;
+    assert!(matches!(
+        result,
+        Err(AlipanError {
+            inner: crate::ErrorInfo::ContentHashMismatch(..),
+            ..
+        })
+    ));
+    tokio::fs::remove_file(&file_path).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_downloader_resume() -> anyhow::Result<()> {
+    let data = mock_download_data();
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_download_handler(
+        data.clone(),
+        crate::adrive_content_hash(&data),
+        calls.clone(),
+    ))
+    .await?;
+    let file_path = std::env::temp_dir().join("alipan_test_adrive_downloader_resume.txt");
+    let sidecar_path = crate::adrive_download_sidecar_path(&file_path);
+    let client = mock_client(api_host.as_str()).await;
+    let cancellation_token = crate::CancellationToken::new();
+    let progress = Arc::new(MockProgress {
+        cancel: Some(cancellation_token.clone()),
+        ..Default::default()
+    });
+    let result = client
+        .adrive_downloader()
+        .await
+        .drive_id("1")
+        .file_id("f1")
+        .file_path(&file_path)
+        .chunk_size(10 << 10)
+        .concurrency(1)
+        .resume(true)
+        .progress(Arc::new(Box::new(progress) as crate::BoxedTransferProgress))
+        .cancellation_token(cancellation_token)
+        .download()
+        .await;
+    assert!(matches!(
+        result,
+        Err(AlipanError {
+            inner: crate::ErrorInfo::Cancelled,
+            ..
+        })
+    ));
+    let sidecar: crate::AdriveDownloadSidecar =
+        serde_json::from_slice(&tokio::fs::read(&sidecar_path).await?)?;
+    assert_eq!(sidecar.completed, vec![(0, 10 << 10)]);
+    calls.lock().unwrap().clear();
+    let progress = Arc::new(MockProgress::default());
+    client
+        .adrive_downloader()
+        .await
+        .drive_id("1")
+        .file_id("f1")
+        .file_path(&file_path)
+        .chunk_size(10 << 10)
+        .resume(true)
+        .progress(Arc::new(
+            Box::new(progress.clone()) as crate::BoxedTransferProgress
+        ))
+        .download()
+        .await?;
+    assert_eq!(tokio::fs::read(&file_path).await?, *data);
+    assert!(!tokio::fs::try_exists(&sidecar_path).await?);
+    assert_eq!(
+        *progress.total.lock().unwrap(),
+        Some(Some(data.len() as i64 - (10 << 10)))
+    );
+    // 只下载剩下的 9 个分块，其中一个失败重试了一次
+    let ranges = calls
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|path| path.starts_with("/download"))
+        .count();
+    assert_eq!(ranges, 10);
+    tokio::fs::remove_file(&file_path).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_remote_file_reader() -> anyhow::Result<()> {
+    let data = mock_download_data();
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_download_handler(
+        data.clone(),
+        crate::adrive_content_hash(&data),
+        calls.clone(),
+    ))
+    .await?;
+    let reader = mock_client(api_host.as_str())
+        .await
+        .adrive_remote_file_reader("1", "f1")
+        .await?
+        .read_ahead(16 << 10);
+    // 可以移动到其他任务中
+    let (mut reader, head) = tokio::spawn(async move {
+        let mut reader = reader;
+        let mut head = vec![0u8; 100];
+        reader.read_exact(&mut head).await?;
+        anyhow::Ok((reader, head))
+    })
+    .await??;
+    assert_eq!(head, data[..100]);
+    // 读取缓存中的数据不会发送请求
+    let requests = |calls: &Arc<std::sync::Mutex<Vec<String>>>| {
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| path.starts_with("/download"))
+            .count()
+    };
+    let mut buf = vec![0u8; 1000];
+    reader.read_exact(&mut buf).await?;
+    assert_eq!(buf, data[100..1100]);
+    assert_eq!(requests(&calls), 1);
+    // 第一次读取 20KB 处返回 500，错误交给调用方，再次读取时重新请求
+    reader.seek(std::io::SeekFrom::Start(20 << 10)).await?;
+    assert!(reader.read_exact(&mut buf).await.is_err());
+    reader.seek(std::io::SeekFrom::Start(20 << 10)).await?;
+    reader.read_exact(&mut buf).await?;
+    assert_eq!(buf, data[20 << 10..(20 << 10) + 1000]);
+    let position = reader.seek(std::io::SeekFrom::End(-10)).await?;
+    assert_eq!(position, data.len() as u64 - 10);
+    let mut tail = vec![];
+    reader.read_to_end(&mut tail).await?;
+    assert_eq!(tail, data[data.len() - 10..]);
+    reader.seek(std::io::SeekFrom::Start(0)).await?;
+    let mut all = vec![];
+    reader.read_to_end(&mut all).await?;
+    assert_eq!(all, *data);
+    assert!(reader
+        .seek(std::io::SeekFrom::Current(-1 - data.len() as i64))
+        .await
+        .is_err());
+    // 打开时获取的下载地址已经过期，没有被使用
+    assert!(!calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|path| path == "/download?v=1"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_download_unparsable_expiration() -> anyhow::Result<()> {
+    let data = mock_download_data();
+    // 第一个下载地址的过期时间无法解析，被服务器拒绝后再重新获取
+    let server = |calls: Arc<std::sync::Mutex<Vec<String>>>| {
+        let inner = mock_download_handler(data.clone(), crate::adrive_content_hash(&data), calls);
+        mock_server(Arc::new(move |request: MockRequest| {
+            let (code, body) = inner(request);
+            (code, body.replace("2000-01-01T00:00:00.000Z", "soon"))
+        }))
+    };
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = server(calls.clone()).await?;
+    let file_path = std::env::temp_dir().join("alipan_test_adrive_download_expiration.txt");
+    mock_client(api_host.as_str())
+        .await
+        .adrive_downloader()
+        .await
+        .drive_id("1")
+        .file_id("f1")
+        .file_path(&file_path)
+        .chunk_size(50 << 10)
+        .download()
+        .await?;
+    assert_eq!(tokio::fs::read(&file_path).await?, *data);
+    tokio::fs::remove_file(&file_path).await?;
+    assert!(calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|path| path == "/download?v=1"));
+
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = server(calls.clone()).await?;
+    let mut reader = mock_client(api_host.as_str())
+        .await
+        .adrive_remote_file_reader("1", "f1")
+        .await?
+        .read_ahead(50 << 10);
+    let mut all = vec![];
+    reader.read_to_end(&mut all).await?;
+    assert_eq!(all, *data);
+    assert!(calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|path| path == "/download?v=1"));
+    Ok(())
+}
+
+/// 模拟 list，按 limit 分页，marker 为下一页开始的序号
+fn mock_list_handler(
+    folders: std::collections::HashMap<String, Vec<serde_json::Value>>,
+    calls: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+) -> MockHandler {
+    Arc::new(move |request: MockRequest| match request.path.as_str() {
+        "/adrive/v1.0/openFile/list" => {
+            let json = request.json();
+            calls.lock().unwrap().push(json.clone());
+            let items = folders
+                .get(json["parent_file_id"].as_str().unwrap_or_default())
+                .cloned()
+                .unwrap_or_default();
+            let limit = json["limit"].as_u64().unwrap_or(50) as usize;
+            let start: usize = json["marker"].as_str().unwrap_or("0").parse().unwrap();
+            let end = (start + limit).min(items.len());
+            let next_marker = if end < items.len() {
+                end.to_string()
+            } else {
+                "".to_string()
+            };
+            let body = serde_json::json!({
+                "items": items[start..end],
+                "next_marker": next_marker,
+            });
+            (200, body.to_string())
+        }
+        _ => (404, "".to_string()),
+    })
+}
+
+fn mock_list_item(parent_file_id: &str, file_id: &str, folder: bool) -> serde_json::Value {
+    let mut item = mock_file_json(1, "");
+    item["parent_file_id"] = parent_file_id.into();
+    item["file_id"] = file_id.into();
+    item["name"] = file_id.into();
+    item["type"] = if folder { "folder" } else { "file" }.into();
+    item
+}
+
+#[tokio::test]
+async fn test_adrive_open_file_list_into_stream() -> anyhow::Result<()> {
+    use futures::TryStreamExt;
+    let items = (0..7)
+        .map(|i| mock_list_item("root", &format!("f{}", i), false))
+        .collect::<Vec<_>>();
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_list_handler(
+        [("root".to_string(), items)].into_iter().collect(),
+        calls.clone(),
+    ))
+    .await?;
+    let stream = mock_client(api_host.as_str())
+        .await
+        .adrive_open_file_list()
+        .await
+        .drive_id("1")
+        .limit(3)
+        .into_stream();
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+    let files = assert_send(stream).try_collect::<Vec<_>>().await?;
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| file.file_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["f0", "f1", "f2", "f3", "f4", "f5", "f6"]
+    );
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 3);
+    assert!(calls.iter().all(|call| call["limit"] == 3));
+    assert_eq!(calls[2]["marker"], "6");
+    Ok(())
+}
+
+async fn walk_paths(walker: crate::AdriveWalker<'_>) -> anyhow::Result<Vec<String>> {
+    use futures::TryStreamExt;
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+    let entries = assert_send(walker.into_stream())
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| format!("{}:{}", entry.depth, entry.path))
+        .collect())
+}
+
+#[tokio::test]
+async fn test_adrive_walker() -> anyhow::Result<()> {
+    // root: a/, b.txt, c/；a: a1.txt, a2/；a2: deep.txt；c: c1.txt
+    let folders = [
+        ("root", vec![("a", true), ("b.txt", false), ("c", true)]),
+        ("a", vec![("a1.txt", false), ("a2", true)]),
+        ("a2", vec![("deep.txt", false)]),
+        ("c", vec![("c1.txt", false)]),
+    ]
+    .into_iter()
+    .map(|(parent, items)| {
+        (
+            parent.to_string(),
+            items
+                .into_iter()
+                .map(|(name, folder)| mock_list_item(parent, name, folder))
+                .collect(),
+        )
+    })
+    .collect::<std::collections::HashMap<_, _>>();
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_list_handler(folders, calls.clone())).await?;
+    let client = mock_client(api_host.as_str()).await;
+    // 并发列出时按完成的顺序返回
+    let mut entries = walk_paths(client.adrive_walker().await.drive_id("1").limit(2)).await?;
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            "1:a",
+            "1:b.txt",
+            "1:c",
+            "2:a/a1.txt",
+            "2:a/a2",
+            "2:c/c1.txt",
+            "3:a/a2/deep.txt"
+        ]
+    );
+    // 每个文件夹一次，root 有 3 项需要两页
+    assert_eq!(calls.lock().unwrap().len(), 5);
+    let entries = walk_paths(client.adrive_walker().await.drive_id("1").concurrency(1)).await?;
+    assert_eq!(
+        entries,
+        vec![
+            "1:a",
+            "1:b.txt",
+            "1:c",
+            "2:a/a1.txt",
+            "2:a/a2",
+            "2:c/c1.txt",
+            "3:a/a2/deep.txt"
+        ]
+    );
+    let entries = walk_paths(
+        client
+            .adrive_walker()
+            .await
+            .drive_id("1")
+            .order(crate::AdriveWalkOrder::DepthFirst)
+            .concurrency(1),
+    )
+    .await?;
+    assert_eq!(
+        entries,
+        vec![
+            "1:a",
+            "1:b.txt",
+            "1:c",
+            "2:a/a1.txt",
+            "2:a/a2",
+            "3:a/a2/deep.txt",
+            "2:c/c1.txt"
+        ]
+    );
+    let entries = walk_paths(client.adrive_walker().await.drive_id("1").max_depth(1)).await?;
+    assert_eq!(entries, vec!["1:a", "1:b.txt", "1:c"]);
+    let entries = walk_paths(
+        client
+            .adrive_walker()
+            .await
+            .drive_id("1")
+            .filter(|entry| entry.path != "a"),
+    )
+    .await?;
+    assert_eq!(entries, vec!["1:b.txt", "1:c", "2:c/c1.txt"]);
+    Ok(())
+}
+
+/// 模拟一个内存中的目录树，支持 get_by_path / create / list / move / copy / trash
+///
+/// 每项为 (file_id, parent_file_id, name, 是否文件夹)
+type MockFsFile = (String, String, String, bool);
+
+fn mock_fs_handler(
+    files: Arc<std::sync::Mutex<Vec<MockFsFile>>>,
+    calls: Arc<std::sync::Mutex<Vec<String>>>,
+) -> MockHandler {
+    let next_id = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    Arc::new(move |request: MockRequest| {
+        calls.lock().unwrap().push(request.path.clone());
+        let json = request.json();
+        let mut files = files.lock().unwrap();
+        let item = |file: &MockFsFile| {
+            let mut item = mock_list_item(&file.1, &file.0, file.3);
+            item["name"] = file.2.as_str().into();
+            item
+        };
+        let child = |files: &[MockFsFile], parent: &str, name: &str| {
+            files
+                .iter()
+                .position(|file| file.1 == parent && file.2 == name)
+        };
+        let not_found = (
+            404,
+            r#"{"code":"NotFound.File","message":"file not exist"}"#.to_string(),
+        );
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/get_by_path" => {
+                let mut parent = "root".to_string();
+                let mut found = None;
+                for name in json["file_path"].as_str().unwrap().split('/').skip(1) {
+                    match child(&files, &parent, name) {
+                        Some(index) => {
+                            parent = files[index].0.clone();
+                            found = Some(index);
+                        }
+                        None => return not_found,
+                    }
+                }
+                match found {
+                    Some(index) => (200, item(&files[index]).to_string()),
+                    None => not_found,
+                }
+            }
+            "/adrive/v1.0/openFile/create" => {
+                assert_eq!(json["type"], "folder");
+                assert_eq!(json["check_name_mode"], "refuse");
+                let parent = json["parent_file_id"].as_str().unwrap().to_string();
+                let name = json["name"].as_str().unwrap().to_string();
+                let (file_id, exist) = match child(&files, &parent, &name) {
+                    Some(index) => (files[index].0.clone(), true),
+                    None => {
+                        let file_id = format!(
+                            "new{}",
+                            next_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                        );
+                        files.push((file_id.clone(), parent.clone(), name.clone(), true));
+                        (file_id, false)
+                    }
+                };
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "file_id": file_id,
+                    "parent_file_id": parent,
+                    "file_name": name,
+                    "available": true,
+                    "exist": exist,
+                    "rapid_upload": false,
+                    "part_info_list": null,
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/list" => {
+                let parent = json["parent_file_id"].as_str().unwrap();
+                let items = files
+                    .iter()
+                    .filter(|file| file.1 == parent)
+                    .map(item)
+                    .collect::<Vec<_>>();
+                let body = serde_json::json!({"items": items, "next_marker": ""});
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/move" => {
+                let file_id = json["file_id"].as_str().unwrap();
+                let parent = json["to_parent_file_id"].as_str().unwrap().to_string();
+                let index = files.iter().position(|file| file.0 == file_id).unwrap();
+                let name = json["new_name"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| files[index].2.clone());
+                let exist = child(&files, &parent, &name).is_some();
+                if !exist {
+                    files[index].1 = parent;
+                    files[index].2 = name;
+                }
+                let body = serde_json::json!({
+                    "drive_id": "1",
+                    "file_id": file_id,
+                    "exist": exist,
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/copy" => {
+                let file_id = json["file_id"].as_str().unwrap();
+                let index = files.iter().position(|file| file.0 == file_id).unwrap();
+                let mut copied = files[index].clone();
+                copied.0 = format!("{}-copy", file_id);
+                copied.1 = json["to_parent_file_id"].as_str().unwrap().to_string();
+                files.push(copied.clone());
+                let body = serde_json::json!({"drive_id": "1", "file_id": copied.0});
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/recyclebin/trash" => {
+                let file_id = json["file_id"].as_str().unwrap();
+                files.retain(|file| file.0 != file_id);
+                let body = serde_json::json!({"drive_id": "1", "file_id": file_id});
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_adrive_fs() -> anyhow::Result<()> {
+    let files = Arc::new(std::sync::Mutex::new(vec![
+        ("backups".into(), "root".into(), "backups".into(), true),
+        ("db".into(), "backups".into(), "db.tar".into(), false),
+    ]));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_fs_handler(files.clone(), calls)).await?;
+    let client = mock_client(api_host.as_str()).await;
+    let fs = client.adrive_fs("1").await;
+
+    assert_eq!(fs.stat("/backups//db.tar").await?.file_id, "db");
+    let err = fs.stat("/backups/missing").await.unwrap_err();
+    assert!(
+        matches!(&err.inner, crate::ErrorInfo::PathNotFound(path) if path == "/backups/missing")
+    );
+
+    // backups 已经存在，只会创建 2026 和 daily
+    let daily = fs.mkdir_p("/backups/2026/daily/").await?;
+    assert_eq!(daily, "new1");
+    assert_eq!(fs.mkdir_p("backups/2026/daily").await?, daily);
+    assert_eq!(fs.resolve("/").await?, "root");
+
+    let moved = fs
+        .rename("/backups/db.tar", "/backups/2026/db-1.tar")
+        .await?;
+    assert!(!moved.exist);
+    assert_eq!(fs.stat("/backups/2026/db-1.tar").await?.file_id, "db");
+    assert!(matches!(
+        fs.rename("/backups/db.tar", "/db.tar")
+            .await
+            .unwrap_err()
+            .inner,
+        crate::ErrorInfo::PathNotFound(_)
+    ));
+
+    let copied = fs.copy("/backups/2026/db-1.tar", "/backups").await?;
+    assert_eq!(copied.file_id, "db-copy");
+    let names = fs
+        .read_dir("/backups")
+        .await?
+        .into_iter()
+        .map(|file| file.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["2026", "db-1.tar"]);
+
+    fs.remove("/backups/2026/db-1.tar").await?;
+    assert!(fs
+        .read_dir("/backups/2026")
+        .await?
+        .iter()
+        .all(|f| f.file_id != "db"));
+    assert!(matches!(
+        fs.read_dir("/missing").await.unwrap_err().inner,
+        crate::ErrorInfo::PathNotFound(_)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_adrive_path_cache() {
+    let cache = crate::AdrivePathCache::new(std::time::Duration::from_secs(60)).max_entries(3);
+    assert_eq!(cache.file_id("1", "/").as_deref(), Some("root"));
+    cache.insert("1", "/a", "a");
+    cache.insert_child("1", "a", "b", "b");
+    cache.insert_child("1", "b", "c.txt", "c");
+    // 父文件夹未知时不会记录
+    cache.insert_child("1", "unknown", "d", "d");
+    assert_eq!(cache.file_id("1", "a//b/./c.txt").as_deref(), Some("c"));
+    assert_eq!(cache.path("1", "c").as_deref(), Some("/a/b/c.txt"));
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.file_id("2", "/a"), None);
+
+    // 删除 b 时 c.txt 也被删除
+    cache.invalidate("1", "b");
+    assert_eq!(cache.file_id("1", "/a").as_deref(), Some("a"));
+    assert_eq!(cache.file_id("1", "/a/b/c.txt"), None);
+    assert_eq!(cache.path("1", "c"), None);
+
+    // 文件移动后原来的下级路径不再可信
+    cache.insert("1", "/a/b", "b");
+    cache.insert("1", "/a/b/c.txt", "c");
+    cache.insert("1", "/x/b", "b");
+    assert_eq!(cache.file_id("1", "/a/b/c.txt"), None);
+    assert_eq!(cache.path("1", "b").as_deref(), Some("/x/b"));
+
+    // 超出数量时淘汰最早写入的
+    cache.insert("1", "/e", "e");
+    cache.insert("1", "/f", "f");
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.file_id("1", "/a"), None);
+    assert_eq!(cache.file_id("1", "/f").as_deref(), Some("f"));
+
+    // 不知道路径的文件被修改时清空这个 drive
+    cache.insert("2", "/g", "g");
+    cache.invalidate("1", "unknown");
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.file_id("2", "/g").as_deref(), Some("g"));
+
+    let expired = crate::AdrivePathCache::new(std::time::Duration::ZERO);
+    expired.insert("1", "/a", "a");
+    assert_eq!(expired.file_id("1", "/a"), None);
+    assert!(expired.is_empty());
+}
+
+#[tokio::test]
+async fn test_adrive_fs_path_cache() -> anyhow::Result<()> {
+    let files = Arc::new(std::sync::Mutex::new(vec![
+        ("backups".into(), "root".into(), "backups".into(), true),
+        ("db".into(), "backups".into(), "db.tar".into(), false),
+    ]));
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_fs_handler(files.clone(), calls.clone())).await?;
+    let cache = crate::AdrivePathCache::new(std::time::Duration::from_secs(60));
+    let client = mock_client(api_host.as_str())
+        .await
+        .set_path_cache(cache.clone())
+        .await;
+    let fs = client.adrive_fs("1").await;
+    let take_calls = || std::mem::take(&mut *calls.lock().unwrap());
+
+    fs.read_dir("/backups").await?;
+    assert_eq!(
+        take_calls(),
+        vec![
+            "/adrive/v1.0/openFile/get_by_path",
+            "/adrive/v1.0/openFile/list"
+        ]
+    );
+    // list 的结果已经写入缓存
+    assert_eq!(fs.resolve("/backups/db.tar").await?, "db");
+    fs.read_dir("/backups").await?;
+    assert_eq!(take_calls(), vec!["/adrive/v1.0/openFile/list"]);
+
+    // backups 来自缓存，2026 由 create 写入缓存
+    let folder = fs.mkdir_p("/backups/2026").await?;
+    assert_eq!(take_calls(), vec!["/adrive/v1.0/openFile/create"]);
+    assert_eq!(
+        cache.path("1", folder.as_str()).as_deref(),
+        Some("/backups/2026")
+    );
+
+    fs.rename("/backups/db.tar", "/backups/2026/db.tar").await?;
+    assert_eq!(take_calls(), vec!["/adrive/v1.0/openFile/move"]);
+    assert_eq!(cache.file_id("1", "/backups/db.tar"), None);
+    assert!(matches!(
+        fs.resolve("/backups/db.tar").await.unwrap_err().inner,
+        crate::ErrorInfo::PathNotFound(_)
+    ));
+    assert_eq!(fs.resolve("/backups/2026/db.tar").await?, "db");
+    assert_eq!(
+        take_calls(),
+        vec![
+            "/adrive/v1.0/openFile/get_by_path",
+            "/adrive/v1.0/openFile/get_by_path"
+        ]
+    );
+
+    // stat 的结果也写入缓存
+    assert_eq!(
+        cache.file_id("1", "/backups/2026/db.tar").as_deref(),
+        Some("db")
+    );
+
+    // 直接调用 move 也会让旧路径失效
+    client
+        .adrive_open_file_move()
+        .await
+        .drive_id("1")
+        .file_id("db")
+        .to_parent_file_id("backups")
+        .request()
+        .await?;
+    assert_eq!(cache.file_id("1", "/backups/2026/db.tar"), None);
+    assert!(matches!(
+        fs.resolve("/backups/2026/db.tar").await.unwrap_err().inner,
+        crate::ErrorInfo::PathNotFound(_)
+    ));
+    take_calls();
+    // 直接调用 list 的结果同样写入缓存
+    client
+        .adrive_open_file_list()
+        .await
+        .drive_id("1")
+        .parent_file_id("backups")
+        .request()
+        .await?;
+    assert_eq!(take_calls(), vec!["/adrive/v1.0/openFile/list"]);
+    assert_eq!(fs.resolve("/backups/db.tar").await?, "db");
+    assert!(take_calls().is_empty());
+
+    // 移到回收站后文件夹以及下级路径都失效
+    fs.remove("/backups/2026").await?;
+    assert_eq!(take_calls(), vec!["/adrive/v1.0/openFile/recyclebin/trash"]);
+    assert_eq!(cache.file_id("1", "/backups/2026"), None);
+    assert_eq!(cache.file_id("1", "/backups/2026/db.tar"), None);
+    assert_eq!(cache.file_id("1", "/backups").as_deref(), Some("backups"));
+    Ok(())
+}
+
+/// 模拟 async_task/get，任务 running-N 先返回 N 次 Running 再返回 Succeed，failed 返回 Failed
+fn mock_async_task_handler(calls: Arc<std::sync::Mutex<Vec<String>>>) -> MockHandler {
+    Arc::new(move |request: MockRequest| match request.path.as_str() {
+        "/adrive/v1.0/openFile/async_task/get" => {
+            let async_task_id = request.json()["async_task_id"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            let mut calls = calls.lock().unwrap();
+            calls.push(async_task_id.clone());
+            let polled = calls.iter().filter(|id| **id == async_task_id).count();
+            let state = match async_task_id.strip_prefix("running-") {
+                Some(running) if polled <= running.parse().unwrap() => "Running",
+                Some(_) => "Succeed",
+                None => "Failed",
+            };
+            let body = serde_json::json!({"state": state, "async_task_id": async_task_id});
+            (200, body.to_string())
+        }
+        _ => (404, "".to_string()),
+    })
+}
+
+#[tokio::test]
+async fn test_adrive_async_task_waiter() -> anyhow::Result<()> {
+    use std::time::Duration;
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let api_host = mock_server(mock_async_task_handler(calls.clone())).await?;
+    let client = mock_client(api_host.as_str()).await;
+    let waiter = client
+        .adrive_async_task_waiter()
+        .await
+        .initial_interval(Duration::from_millis(10))
+        .max_interval(Duration::from_millis(20));
+    let task = |async_task_id: Option<&str>| crate::AdriveOpenFileCopy {
+        drive_id: "1".to_string(),
+        file_id: "f".to_string(),
+        async_task_id: async_task_id.map(str::to_string),
+    };
+
+    // 同步完成的操作不会查询
+    waiter.wait_until_done(&task(None)).await?;
+    assert!(calls.lock().unwrap().is_empty());
+
+    let start = std::time::Instant::now();
+    waiter.wait_until_done(&task(Some("running-3"))).await?;
+    // 间隔 10 + 20 + 20
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(calls.lock().unwrap().len(), 4);
+
+    let err = waiter
+        .wait_until_done(&task(Some("failed")))
+        .await
+        .unwrap_err();
+    assert!(matches!(&err.inner, crate::ErrorInfo::AsyncTaskFailed(id) if id == "failed"));
+
+    let err = waiter
+        .timeout(Duration::from_millis(30))
+        .wait_until_done(&task(Some("running-100")))
+        .await
+        .unwrap_err();
+    assert!(matches!(&err.inner, crate::ErrorInfo::AsyncTaskTimeout(id) if id == "running-100"));
+    Ok(())
+}
+
+#[test]
+fn test_adrive_search_query() {
+    use crate::{AdriveSearchOperator, AdriveSearchQuery};
+    let query =
+        AdriveSearchQuery::name_match("x").and(AdriveSearchQuery::r#type(AdriveOpenFileType::File));
+    assert_eq!(query.as_str(), r#"name match "x" and type = "file""#);
+    let query = AdriveSearchQuery::file_extension("mp4")
+        .or(AdriveSearchQuery::file_extension("mkv"))
+        .and(AdriveSearchQuery::updated_at(
+            AdriveSearchOperator::Ge,
+            chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+                .unwrap()
+                .into(),
+        ));
+    assert_eq!(
+        query.to_string(),
+        r#"(file_extension = "mp4" or file_extension = "mkv") and updated_at >= "2026-01-02T03:04:05""#
+    );
+    // 引号和反斜杠被转义，不会改变查询语句的结构
+    let query = AdriveSearchQuery::name(r#"a" or name match "\"#);
+    assert_eq!(query.as_str(), r#"name = "a\" or name match \"\\""#);
+}
+
+#[tokio::test]
+async fn test_adrive_open_file_search() -> anyhow::Result<()> {
+    use futures::TryStreamExt;
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_calls = calls.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/search" => {
+                let json = request.json();
+                handler_calls.lock().unwrap().push(json.clone());
+                let (items, next_marker) = match json["marker"].as_str() {
+                    None => (vec![mock_list_item("root", "s0", false)], "m1"),
+                    Some(_) => (vec![mock_list_item("a", "s1", false)], ""),
+                };
+                let body = serde_json::json!({
+                    "items": items,
+                    "next_marker": next_marker,
+                    "total_count": 2,
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let client = mock_client(api_host.as_str()).await;
+    let page = client
+        .adrive_open_file_search()
+        .await
+        .drive_id("1")
+        .query(crate::AdriveSearchQuery::name_match("s"))
+        .return_total_count(true)
+        .request()
+        .await?;
+    assert_eq!(page.total_count, Some(2));
+    assert_eq!(page.next_marker.as_deref(), Some("m1"));
+    let files = client
+        .adrive_open_file_search()
+        .await
+        .drive_id("1")
+        .query(crate::AdriveSearchQuery::name_match("s"))
+        .order_by("updated_at DESC")
+        .limit(1)
+        .into_stream()
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| file.file_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["s0", "s1"]
+    );
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 3);
+    assert_eq!(calls[1]["query"], r#"name match "s""#);
+    assert_eq!(calls[1]["order_by"], "updated_at DESC");
+    assert_eq!(calls[2]["marker"], "m1");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_open_file_starred_list() -> anyhow::Result<()> {
+    use futures::TryStreamExt;
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_calls = calls.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/starredList" => {
+                let json = request.json();
+                handler_calls.lock().unwrap().push(json.clone());
+                let (file_id, next_marker) = match json["marker"].as_str() {
+                    None => ("star0", "m1"),
+                    Some(_) => ("star1", ""),
+                };
+                let mut item = mock_list_item("root", file_id, false);
+                item["starred"] = true.into();
+                let body = serde_json::json!({"items": [item], "next_marker": next_marker});
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let files = mock_client(api_host.as_str())
+        .await
+        .adrive_open_file_starred_list()
+        .await
+        .drive_id("1")
+        .limit(1)
+        .image_thumbnail_width(480)
+        .into_stream()
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| file.file_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["star0", "star1"]
+    );
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0]["image_thumbnail_width"], 480);
+    assert_eq!(calls[1]["marker"], "m1");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_open_file_recyclebin_restore_mock() -> anyhow::Result<()> {
+    let api_host = mock_server(Arc::new(|request: MockRequest| {
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/recyclebin/restore" => {
+                let json = request.json();
+                let body = serde_json::json!({
+                    "drive_id": json["drive_id"],
+                    "file_id": json["file_id"],
+                    "async_task_id": "task",
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/delete" => {
+                let json = request.json();
+                let body = serde_json::json!({
+                    "drive_id": json["drive_id"],
+                    "file_id": json["file_id"],
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let client = mock_client(api_host.as_str()).await;
+    let restore: crate::AdriveOpenFileRecyclebinRestore = client
+        .adrive_open_file_recyclebin_restore()
+        .await
+        .drive_id("1")
+        .file_id("f")
+        .request()
+        .await?;
+    assert_eq!(restore.file_id, "f");
+    assert_eq!(
+        crate::AdriveAsyncTask::async_task_id(&restore),
+        Some("task")
+    );
+    let delete: crate::AdriveOpenFileDelete = client
+        .adrive_open_file_delete()
+        .await
+        .drive_id("1")
+        .file_id("f")
+        .request()
+        .await?;
+    assert_eq!(delete.async_task_id, None);
+    Ok(())
+}
+
+#[test]
+fn test_adrive_open_file_video_metadata() -> anyhow::Result<()> {
+    let mut json = mock_file_json(1, "");
+    json["video_media_metadata"] = serde_json::json!({
+        "width": 1920,
+        "height": "1080",
+        "duration": 12.5,
+        "time": "2024-01-01T00:00:00Z",
+        "video_media_video_stream": [{"code_name": "h264", "fps": "30/1", "bitrate": 4000000}],
+        "video_media_audio_stream": [{"code_name": "aac", "channels": 2, "sample_rate": "44100"}],
+    });
+    json["video_preview_metadata"] = serde_json::json!({
+        "duration": "12.5",
+        "width": 1920,
+        "height": 1080,
+        "template_list": [
+            {"template_id": "FHD", "status": "finished", "unknown": 1},
+            {"status": "running", "template_id": null, "expiration": "1700000000"},
+            {"status": "running"}
+        ],
+        "unknown": {"nested": true},
+    });
+    let file: crate::AdriveOpenFile = serde_json::from_value(json)?;
+    let media = file.video_media_metadata.as_ref().unwrap();
+    assert_eq!(media.height, Some(1080));
+    assert_eq!(media.duration.as_deref(), Some("12.5"));
+    assert_eq!(media.extra["time"], "2024-01-01T00:00:00Z");
+    assert_eq!(
+        media.video_media_video_stream[0].bitrate.as_deref(),
+        Some("4000000")
+    );
+    assert_eq!(media.video_media_audio_stream[0].channels, Some(2));
+    let preview = file.video_preview_metadata.as_ref().unwrap();
+    assert_eq!(preview.template_list[0].template_id, "FHD");
+    assert!(preview.template_list[0].is_finished());
+    // 缺少的字段和无法解析的时间不影响整个文件的解析
+    assert_eq!(preview.template_list[1].template_id, "");
+    assert_eq!(preview.template_list[1].expiration, None);
+    assert_eq!(preview.template_list[2].template_id, "");
+    assert_eq!(preview.extra["unknown"]["nested"], true);
+    // 未知字段在序列化时保留
+    let value = serde_json::to_value(&file)?;
+    assert_eq!(value["video_preview_metadata"]["unknown"]["nested"], true);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adrive_open_file_get_video_preview_play_info() -> anyhow::Result<()> {
+    let api_host = mock_server(Arc::new(|request: MockRequest| {
+        let json = request.json();
+        let tasks = serde_json::json!([
+            {"template_id": "FHD", "template_width": 1920, "template_height": 1080, "status": "finished", "url": "https://play/fhd.m3u8"},
+            {"template_id": "LD", "template_width": 640, "template_height": 360, "status": "finished", "url": "https://play/ld.m3u8"},
+            {"template_id": "QHD", "template_width": 2560, "template_height": 1440, "status": "running"},
+        ]);
+        match request.path.as_str() {
+            "/adrive/v1.0/openFile/getVideoPreviewPlayInfo" => {
+                assert_eq!(json["category"], "live_transcoding");
+                let body = serde_json::json!({
+                    "domain_id": "d",
+                    "drive_id": json["drive_id"],
+                    "file_id": json["file_id"],
+                    "video_preview_play_info": {
+                        "category": "live_transcoding",
+                        "meta": {"duration": 12.5, "width": 1920, "height": 1080},
+                        "live_transcoding_task_list": tasks,
+                        "live_transcoding_subtitle_task_list": [
+                            {"language": "chi", "status": "finished", "url": "https://play/chi.vtt"}
+                        ],
+                    },
+                });
+                (200, body.to_string())
+            }
+            "/adrive/v1.0/openFile/getVideoPreviewPlayMeta" => {
+                let body = serde_json::json!({
+                    "drive_id": json["drive_id"],
+                    "file_id": json["file_id"],
+                    "video_preview_play_meta": {
+                        "category": "live_transcoding",
+                        "meta": {"duration": "12.5"},
+                        "live_transcoding_task_list": tasks,
+                    },
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let client = mock_client(api_host.as_str()).await;
+    let before = chrono::Utc::now();
+    let play_info = client
+        .adrive_open_file_get_video_preview_play_info()
+        .await
+        .drive_id("1")
+        .file_id("v")
+        .get_subtitle_info(true)
+        .url_expire_sec(600)
+        .request()
+        .await?;
+    let info = &play_info.video_preview_play_info;
+    assert_eq!(
+        info.meta.as_ref().unwrap().duration.as_deref(),
+        Some("12.5")
+    );
+    assert_eq!(
+        info.finished_tasks()
+            .iter()
+            .map(|task| task.template_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["LD", "FHD"]
+    );
+    let fhd = info.task("FHD").unwrap();
+    let expiration = fhd.expiration.unwrap();
+    assert!(expiration >= before + chrono::Duration::seconds(600));
+    assert!(expiration <= chrono::Utc::now() + chrono::Duration::seconds(600));
+    assert_eq!(info.task("QHD").unwrap().expiration, None);
+    assert!(info.live_transcoding_subtitle_task_list[0]
+        .expiration
+        .is_some());
+    assert_eq!(play_info.file_id, "v");
+
+    let play_meta = client
+        .adrive_open_file_get_video_preview_play_meta()
+        .await
+        .drive_id("1")
+        .file_id("v")
+        .request()
+        .await?;
+    let meta = &play_meta.video_preview_play_meta;
+    assert_eq!(meta.live_transcoding_task_list.len(), 3);
+    assert_eq!(meta.live_transcoding_task_list[2].url, None);
+    Ok(())
+}
+
+#[test]
+fn test_oauth_pkce() -> anyhow::Result<()> {
+    // base64url(sha256(code_verifier))，不带填充
+    let pkce = crate::OauthPkce::s256("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWjCpAOM");
+    assert_eq!(
+        pkce.code_challenge,
+        "EVbp2KWcC9NYvSZF71xiHO4T7f3Q5AzQmQ2dsXPCYrc"
+    );
+    assert_eq!(pkce.code_challenge_method, "S256");
+    let generated = crate::OauthPkce::new()?;
+    assert_eq!(generated.code_verifier.len(), 43);
+    assert_ne!(
+        generated.code_verifier,
+        crate::OauthPkce::new()?.code_verifier
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oauth_pkce_flow() -> anyhow::Result<()> {
+    let forms = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_forms = forms.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/oauth/access_token" => {
+                let form = url::form_urlencoded::parse(&request.body)
+                    .into_owned()
+                    .collect::<std::collections::HashMap<String, String>>();
+                handler_forms.lock().unwrap().push(form);
+                let body = serde_json::json!({
+                    "token_type": "Bearer",
+                    "access_token": "access",
+                    "refresh_token": "refresh",
+                    "expires_in": 7200,
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let oauth_client = OAuthClient::default()
+        .set_api_host(api_host.as_str())
+        .await
+        .set_client_id("client")
+        .await;
+    let pkce = crate::OauthPkce::s256("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWjCpAOM");
+    let url = oauth_client
+        .oauth_authorize()
+        .await
+        .redirect_uri("http://127.0.0.1/callback")
+        .scope("user:base")
+        .pkce(&pkce)
+        .build()?;
+    let url = url::Url::parse(url.as_str())?;
+    let query = url
+        .query_pairs()
+        .into_owned()
+        .collect::<std::collections::HashMap<_, _>>();
+    assert_eq!(query["code_challenge"], pkce.code_challenge);
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    // 没有 client_secret 时必须提交 code_verifier
+    let err = oauth_client
+        .oauth_access_token()
+        .await
+        .grant_type(GrantType::AuthorizationCode)
+        .code("code")
+        .request()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.inner,
+        crate::ErrorInfo::RequireParamMissing(_)
+    ));
+    let token = oauth_client
+        .oauth_access_token()
+        .await
+        .grant_type(GrantType::AuthorizationCode)
+        .code("code")
+        .code_verifier(pkce.code_verifier.as_str())
+        .request()
+        .await?;
+    assert_eq!(token.access_token, "access");
+    let forms = forms.lock().unwrap();
+    assert_eq!(forms.len(), 1);
+    assert_eq!(forms[0]["code_verifier"], pkce.code_verifier);
+    assert!(!forms[0].contains_key("client_secret"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oauth_qrcode_login() -> anyhow::Result<()> {
+    let statuses = Arc::new(std::sync::Mutex::new(vec![
+        "LoginSuccess",
+        "ScanSuccess",
+        "WaitLogin",
+    ]));
+    let forms = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_statuses = statuses.clone();
+    let handler_forms = forms.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/oauth/authorize/qrcode" => {
+                let body = request.json();
+                assert_eq!(body["client_id"], "client");
+                assert_eq!(body["scopes"], serde_json::json!(["user:base"]));
+                let body = serde_json::json!({
+                    "qrCodeUrl": "https://example.com/qrcode.png",
+                    "sid": "sid",
+                });
+                (200, body.to_string())
+            }
+            "/oauth/qrcode/sid/status" => {
+                let status = handler_statuses
+                    .lock()
+                    .unwrap()
+                    .pop()
+                    .unwrap_or("WaitLogin");
+                let body = if status == "LoginSuccess" {
+                    serde_json::json!({"status": status, "authCode": "code"})
+                } else {
+                    serde_json::json!({"status": status})
+                };
+                (200, body.to_string())
+            }
+            "/oauth/qrcode/expired/status" => (
+                200,
+                serde_json::json!({"status": "QRCodeExpired"}).to_string(),
+            ),
+            "/oauth/access_token" => {
+                let form = url::form_urlencoded::parse(&request.body)
+                    .into_owned()
+                    .collect::<std::collections::HashMap<String, String>>();
+                handler_forms.lock().unwrap().push(form);
+                let body = serde_json::json!({
+                    "token_type": "Bearer",
+                    "access_token": "access",
+                    "refresh_token": "refresh",
+                    "expires_in": 7200,
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let oauth_client = OAuthClient::default()
+        .set_api_host(api_host.as_str())
+        .await
+        .set_client_id("client")
+        .await;
+    let pkce = crate::OauthPkce::s256("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWjCpAOM");
+    let session = oauth_client
+        .oauth_qrcode_login()
+        .await
+        .scopes(["user:base"])
+        .pkce(pkce.clone())
+        .poll_interval(std::time::Duration::from_millis(10))
+        .start()
+        .await?;
+    assert_eq!(session.qr_code_url(), "https://example.com/qrcode.png");
+    assert_eq!(session.sid(), "sid");
+    let token = session.wait().await?;
+    assert_eq!(token.access_token, "access");
+    assert!(statuses.lock().unwrap().is_empty());
+    {
+        let forms = forms.lock().unwrap();
+        assert_eq!(forms.len(), 1);
+        assert_eq!(forms[0]["code"], "code");
+        assert_eq!(forms[0]["code_verifier"], pkce.code_verifier);
+    }
+
+    // 一直没有扫码
+    let err = oauth_client
+        .oauth_qrcode_login()
+        .await
+        .scopes(["user:base"])
+        .poll_interval(std::time::Duration::from_millis(10))
+        .timeout(std::time::Duration::from_millis(50))
+        .start()
+        .await?
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::QrCodeLoginTimeout(_)));
+
+    // 二维码过期
+    let mut session = oauth_client
+        .oauth_qrcode_login()
+        .await
+        .scopes(["user:base"])
+        .poll_interval(std::time::Duration::from_millis(10))
+        .start()
+        .await?;
+    session.qrcode.sid = "expired".to_string();
+    let err = session.wait().await.unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::QrCodeExpired(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oauth_loopback_login() -> anyhow::Result<()> {
+    let forms = Arc::new(std::sync::Mutex::new(vec![]));
+    let handler_forms = forms.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/oauth/access_token" => {
+                let form = url::form_urlencoded::parse(&request.body)
+                    .into_owned()
+                    .collect::<std::collections::HashMap<String, String>>();
+                handler_forms.lock().unwrap().push(form);
+                let body = serde_json::json!({
+                    "token_type": "Bearer",
+                    "access_token": "access",
+                    "refresh_token": "refresh",
+                    "expires_in": 7200,
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let oauth_client = OAuthClient::default()
+        .set_api_host(api_host.as_str())
+        .await
+        .set_client_id("client")
+        .await
+        .set_client_secret("secret")
+        .await;
+    let session = oauth_client
+        .oauth_loopback_login()
+        .await
+        .scope("user:base")
+        .start()
+        .await?;
+    let url = url::Url::parse(session.authorize_url())?;
+    let query = url
+        .query_pairs()
+        .into_owned()
+        .collect::<std::collections::HashMap<_, _>>();
+    assert_eq!(query["redirect_uri"], session.redirect_uri());
+    assert_eq!(query["state"], session.state);
+    assert!(session.redirect_uri().starts_with("http://127.0.0.1:"));
+    assert!(session.redirect_uri().ends_with("/callback"));
+    let redirect_uri = session.redirect_uri().to_string();
+    let state = session.state.clone();
+    // 模拟浏览器的跳转
+    let agent = reqwest::Client::new();
+    let browser = async {
+        let favicon = agent
+            .get(redirect_uri.replace("/callback", "/favicon.ico"))
+            .send()
+            .await?;
+        let callback = agent
+            .get(redirect_uri.as_str())
+            .query(&[("code", "code"), ("state", state.as_str())])
+            .send()
+            .await?;
+        reqwest::Result::Ok((favicon, callback))
+    };
+    let (token, resp) = tokio::join!(session.wait(), browser);
+    let (favicon, resp) = resp?;
+    assert_eq!(favicon.status().as_u16(), 404);
+    let token = token?;
+    assert_eq!(token.access_token, "access");
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().await?, crate::OAUTH_LOOPBACK_SUCCESS_HTML);
+    {
+        let forms = forms.lock().unwrap();
+        assert_eq!(forms.len(), 1);
+        assert_eq!(forms[0]["code"], "code");
+    }
+
+    // state 不一致的回调返回 400，继续等待正确的回调；不发送请求的预连接不影响回调
+    let session = oauth_client
+        .oauth_loopback_login()
+        .await
+        .scope("user:base")
+        .start()
+        .await?;
+    let redirect_uri = session.redirect_uri().to_string();
+    let state = session.state.clone();
+    let preconnect = tokio::net::TcpStream::connect(
+        redirect_uri
+            .trim_start_matches("http://")
+            .trim_end_matches("/callback"),
+    )
+    .await?;
+    let browser = async {
+        let forged = agent
+            .get(redirect_uri.as_str())
+            .query(&[("code", "forged"), ("state", "forged")])
+            .send()
+            .await?;
+        let callback = agent
+            .get(redirect_uri.as_str())
+            .query(&[("code", "code2"), ("state", state.as_str())])
+            .send()
+            .await?;
+        reqwest::Result::Ok((forged, callback))
+    };
+    let (token, resp) = tokio::join!(session.wait(), browser);
+    let (forged, callback) = resp?;
+    assert_eq!(forged.status().as_u16(), 400);
+    assert_eq!(forged.text().await?, crate::OAUTH_LOOPBACK_FAILURE_HTML);
+    assert_eq!(callback.status().as_u16(), 200);
+    assert_eq!(token?.access_token, "access");
+    assert_eq!(forms.lock().unwrap()[1]["code"], "code2");
+    drop(preconnect);
+
+    // 请求头过多的请求被忽略，超时前没有正确的回调
+    let session = oauth_client
+        .oauth_loopback_login()
+        .await
+        .scope("user:base")
+        .timeout(std::time::Duration::from_millis(500))
+        .start()
+        .await?;
+    let mut stream = tokio::net::TcpStream::connect(
+        session
+            .redirect_uri()
+            .trim_start_matches("http://")
+            .trim_end_matches("/callback"),
+    )
+    .await?;
+    let request = format!(
+        "GET /callback?code=code&state={} HTTP/1.1\r\n{}\r\n",
+        session.state,
+        "X-Header: 1\r\n".repeat(200)
+    );
+    tokio::io::AsyncWriteExt::write_all(&mut stream, request.as_bytes()).await?;
+    assert!(matches!(
+        session.wait().await.unwrap_err().inner,
+        crate::ErrorInfo::OauthLoginTimeout
+    ));
+
+    // 用户拒绝授权
+    let session = oauth_client
+        .oauth_loopback_login()
+        .await
+        .scope("user:base")
+        .start()
+        .await?;
+    let redirect_uri = session.redirect_uri().to_string();
+    let browser = agent
+        .get(redirect_uri.as_str())
+        .query(&[("error", "access_denied")])
+        .send();
+    let (result, resp) = tokio::join!(session.wait(), browser);
+    assert_eq!(resp?.status().as_u16(), 400);
+    assert!(matches!(
+        result.unwrap_err().inner,
+        crate::ErrorInfo::OauthCallbackError(ref error, None) if error == "access_denied"
+    ));
+    assert_eq!(forms.lock().unwrap().len(), 2);
+
+    let err = oauth_client
+        .oauth_loopback_login()
+        .await
+        .scope("user:base")
+        .timeout(std::time::Duration::from_millis(50))
+        .start()
+        .await?
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::OauthLoginTimeout));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oauth_state_manager() -> anyhow::Result<()> {
+    let callback = crate::OauthCallback::parse_url(
+        "https://example.com/callback?code=a%2Bb&state=s&error_description=x%20y",
+    )?;
+    assert_eq!(callback.code.as_deref(), Some("a+b"));
+    assert_eq!(callback.state.as_deref(), Some("s"));
+    assert_eq!(callback.error, None);
+    assert_eq!(callback.error_description.as_deref(), Some("x y"));
+    assert_eq!(callback.verify_state("s")?, "a+b");
+    assert!(matches!(
+        callback.verify_state("t").unwrap_err().inner,
+        crate::ErrorInfo::OauthStateMismatch
+    ));
+
+    let manager = crate::OauthStateManager::default();
+    let state = manager.generate().await?;
+    let other = manager.generate().await?;
+    assert_ne!(state, other);
+    let verified = manager
+        .verify_url(format!("/callback?code=code&state={}", state).as_str())
+        .await?;
+    assert_eq!(verified.code, "code");
+    assert_eq!(verified.state, state);
+    assert_eq!(verified.code_verifier, None);
+
+    // state 只能使用一次
+    let err = manager
+        .verify_url(format!("/callback?code=code&state={}", state).as_str())
+        .await
+        .unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::OauthStateMismatch));
+    for url in ["/callback?code=code&state=forged", "/callback?code=code"] {
+        let err = manager.verify_url(url).await.unwrap_err();
+        assert!(matches!(err.inner, crate::ErrorInfo::OauthStateMismatch));
+    }
+
+    // 带有错误的回调也会使 state 失效
+    let err = manager
+        .verify_url(
+            format!(
+                "/callback?error=access_denied&error_description=denied&state={}",
+                other
+            )
+            .as_str(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.inner,
+        crate::ErrorInfo::OauthCallbackError(ref error, Some(ref description))
+            if error == "access_denied" && description == "denied"
+    ));
+    let err = manager
+        .verify_url(format!("/callback?code=code&state={}", other).as_str())
+        .await
+        .unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::OauthStateMismatch));
+
+    let (state, pkce) = manager.generate_with_pkce().await?;
+    let err = manager
+        .verify_url(format!("/callback?state={}", state).as_str())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.inner,
+        crate::ErrorInfo::RequireParamMissing(_)
+    ));
+    let (state, pkce2) = manager.generate_with_pkce().await?;
+    assert_ne!(pkce, pkce2);
+    let verified = manager
+        .verify_url(format!("/callback?code=code&state={}", state).as_str())
+        .await?;
+    assert_eq!(verified.code_verifier, Some(pkce2.code_verifier));
+
+    let manager = crate::OauthStateManager::default().ttl(std::time::Duration::ZERO);
+    let state = manager.generate().await?;
+    let err = manager
+        .verify_url(format!("/callback?code=code&state={}", state).as_str())
+        .await
+        .unwrap_err();
+    assert!(matches!(err.inner, crate::ErrorInfo::OauthStateExpired));
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct MemoryAccessTokenStore {
+    token: std::sync::Mutex<Option<AccessToken>>,
+    reads: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait]
+impl OAuthClientAccessTokenStore for Arc<MemoryAccessTokenStore> {
+    async fn get_access_token(&self) -> anyhow::Result<Option<AccessToken>> {
+        self.reads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(self.token.lock().unwrap().clone())
+    }
+
+    async fn set_access_token(&self, access_token: AccessToken) -> anyhow::Result<()> {
+        *self.token.lock().unwrap() = Some(access_token);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_oauth_access_token_manager_single_flight() -> anyhow::Result<()> {
+    let refreshes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let fail = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let handler_refreshes = refreshes.clone();
+    let handler_fail = fail.clone();
+    let api_host = mock_server(Arc::new(move |request: MockRequest| {
+        match request.path.as_str() {
+            "/oauth/access_token" => {
+                let form = url::form_urlencoded::parse(&request.body)
+                    .into_owned()
+                    .collect::<std::collections::HashMap<String, String>>();
+                assert_eq!(form["grant_type"], "refresh_token");
+                let count = handler_refreshes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                if handler_fail.load(std::sync::atomic::Ordering::SeqCst) {
+                    let body = serde_json::json!({
+                        "code": "InvalidParameter.RefreshToken",
+                        "message": "refresh token is invalid",
+                    });
+                    return (400, body.to_string());
+                }
+                let body = serde_json::json!({
+                    "token_type": "Bearer",
+                    "access_token": format!("access_{}", count + 1),
+                    "refresh_token": format!("refresh_{}", count + 1),
+                    "expires_in": 7200,
+                });
+                (200, body.to_string())
+            }
+            _ => (404, "".to_string()),
+        }
+    }))
+    .await?;
+    let oauth_client = OAuthClient::default()
+        .set_api_host(api_host.as_str())
+        .await
+        .set_client_id("client")
+        .await
+        .set_client_secret("secret")
+        .await;
+    let stale = AccessToken {
+        access_token: "access_0".to_string(),
+        token_type: "Bearer".to_string(),
+        expires_in: 7200,
+        refresh_token: "refresh_0".to_string(),
+        created_at: chrono::Utc::now().timestamp() - 7000,
+    };
+    let store = Arc::new(MemoryAccessTokenStore::default());
+    *store.token.lock().unwrap() = Some(stale.clone());
+    let access_token_store: Box<dyn OAuthClientAccessTokenStore> = Box::new(store.clone());
+    let manager = OAuthClientAccessTokenManager::new(oauth_client, access_token_store);
+
+    // 同时过期的请求只续期一次
+    let tokens = futures::future::join_all(
+        (0..10).map(|_| crate::AccessTokenLoader::get_access_token(&manager)),
+    )
+    .await;
+    for token in tokens {
+        assert_eq!(token?.access_token, "access_1");
+    }
+    assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(store.reads.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(
+        store.token.lock().unwrap().as_ref().unwrap().refresh_token,
+        "refresh_1"
+    );
+
+    // 之后使用内存中的令牌
+    for _ in 0..3 {
+        let token = crate::AccessTokenLoader::get_access_token(&manager).await?;
+        assert_eq!(token.access_token, "access_1");
+    }
+    assert_eq!(store.reads.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // 续期失败时所有等待的请求都得到错误，下次调用重新续期
+    manager.clear_cache();
+    *store.token.lock().unwrap() = Some(stale);
+    fail.store(true, std::sync::atomic::Ordering::SeqCst);
+    let results = futures::future::join_all(
+        (0..5).map(|_| crate::AccessTokenLoader::get_access_token(&manager)),
+    )
+    .await;
+    for result in results {
+        assert!(result.is_err());
+    }
+    assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 2);
+    fail.store(false, std::sync::atomic::Ordering::SeqCst);
+    let token = crate::AccessTokenLoader::get_access_token(&manager).await?;
+    assert_eq!(token.access_token, "access_3");
+    assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 3);
+    Ok(())
+}